@@ -0,0 +1,22 @@
+//! Uses the engine from a `no_std` crate, so building this on its own checks the engine doesn't need `std`
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use chess_engine::board::{MailboxBoard, Move};
+
+/// Generates the legal moves from the starting position
+pub fn starting_moves() -> Vec<Move> {
+    MailboxBoard::starting_position().legal_moves()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starting_moves() {
+        assert_eq!(starting_moves().len(), 20);
+    }
+}
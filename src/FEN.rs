@@ -1,20 +0,0 @@
-
-use crate::board;
-
-/// Representation of FEN in memory
-struct FenRep {
-    board : [String ; 8], // each rank described
-
-    active_color : Player,
-
-}
-
-trait FEN {
-    fn from_FEN()
-}
-
-
-impl Piece {
-
-}
-
@@ -0,0 +1,464 @@
+use alloc::vec::Vec;
+use crate::board::{
+    castle_bit, Board, BOARD_LENGTH, CastleSide, Chessman, Coordinate, MailboxBoard, Move, MoveKind, NUM_CHESSMEN, NUM_TEAMS, Piece, Team,
+};
+use crate::fen::FenError;
+use crate::magic::{bishop_attacks, rook_attacks};
+use crate::movegen::{PROMOTION_CAPTURES, PROMOTIONS};
+use crate::tables::{KING_ATTACKS, KNIGHT_ATTACKS};
+
+/// gets the bit for a square
+fn bit(square: Coordinate) -> u64 {
+    1 << square.index()
+}
+
+/// gets the index of the bitboard holding a team's chessmen
+fn slot(team: Team, chessman: Chessman) -> usize {
+    team as usize * NUM_CHESSMEN + chessman as usize
+}
+
+/// iterates over the squares of the set bits, lowest first
+fn squares(mut bits: u64) -> impl Iterator<Item = Coordinate> {
+    core::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+
+        let square = Coordinate::new_unchecked(bits.trailing_zeros() as u8);
+        bits &= bits - 1;
+        Some(square)
+    })
+}
+
+/// gets the squares a single step by each offset reaches
+fn step_attacks(square: Coordinate, offsets: &[(i8, i8)]) -> u64 {
+    offsets
+        .iter()
+        .filter_map(|&(d_rank, d_file)| square.offset(d_rank, d_file))
+        .fold(0, |attacks, target| attacks | bit(target))
+}
+
+/// gets the squares a pawn of the given team attacks, diagonally forward
+fn pawn_attacks(square: Coordinate, team: Team) -> u64 {
+    match team {
+        Team::White => step_attacks(square, &[(1, -1), (1, 1)]),
+        Team::Black => step_attacks(square, &[(-1, -1), (-1, 1)]),
+    }
+}
+
+/// checks whether any piece of a team attacks a square, given every team's pieces
+fn attacked(pieces: &[u64; NUM_TEAMS * NUM_CHESSMEN], square: Coordinate, by: Team) -> bool {
+    let occupied = pieces.iter().fold(0, |occupied, bits| occupied | bits);
+    let enemy = |chessman: Chessman| pieces[slot(by, chessman)];
+
+    // a pawn attacks the square if a pawn of the other team on the square would attack it back
+    pawn_attacks(square, by.opponent()) & enemy(Chessman::Pawn) != 0
+        || KNIGHT_ATTACKS[square.index() as usize] & enemy(Chessman::Knight) != 0
+        || KING_ATTACKS[square.index() as usize] & enemy(Chessman::King) != 0
+        || rook_attacks(square, occupied) & (enemy(Chessman::Rook) | enemy(Chessman::Queen)) != 0
+        || bishop_attacks(square, occupied) & (enemy(Chessman::Bishop) | enemy(Chessman::Queen)) != 0
+}
+
+/// A board made of one bitboard per team and chessman, with a bit set for each square holding that piece
+///
+/// This is an alternative to `MailboxBoard`, where finding attacks is a matter of masking bits instead of
+/// scanning squares. It doesn't make moves itself, build one from a `MailboxBoard` to generate its moves.
+#[derive(Debug, Clone)]
+pub struct BitBoard {
+    pieces: [u64; NUM_TEAMS * NUM_CHESSMEN],
+    occupancy: [u64; NUM_TEAMS],
+    side_to_move: Team,
+    castling: u8,
+    en_passant: Option<Coordinate>,
+}
+
+impl BitBoard {
+    /// Creates a board with no pieces, White to move and no castling rights
+    pub fn empty() -> Self {
+        BitBoard {
+            pieces: [0; NUM_TEAMS * NUM_CHESSMEN],
+            occupancy: [0; NUM_TEAMS],
+            side_to_move: Team::White,
+            castling: 0,
+            en_passant: None,
+        }
+    }
+
+    /// Parses a board from FEN, see `MailboxBoard::from_fen`
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        MailboxBoard::from_fen(fen).map(|board| BitBoard::from(&board))
+    }
+
+    /// gets the piece on a tile
+    pub fn get_tile(&self, coord: Coordinate) -> Piece {
+        for team in Team::all() {
+            if self.occupancy[team as usize] & bit(coord) == 0 {
+                continue;
+            }
+
+            for chessman in Chessman::all() {
+                if self.pieces[slot(team, chessman)] & bit(coord) != 0 {
+                    return Piece::new(Some((team, chessman)));
+                }
+            }
+        }
+
+        Piece::new(None)
+    }
+
+    /// gets the team whose turn it is
+    pub fn side_to_move(&self) -> Team {
+        self.side_to_move
+    }
+
+    /// checks whether a team still has the right to castle toward a side
+    pub fn can_castle(&self, team: Team, side: CastleSide) -> bool {
+        self.castling & castle_bit(team, side) != 0
+    }
+
+    /// gets the square a pawn can capture onto en passant, if any
+    pub fn en_passant(&self) -> Option<Coordinate> {
+        self.en_passant
+    }
+
+    /// gets the squares with a piece on them, as a bitboard
+    pub fn occupied(&self) -> u64 {
+        self.occupancy[Team::White as usize] | self.occupancy[Team::Black as usize]
+    }
+
+    /// gets the squares with one of a team's pieces on them, as a bitboard
+    pub fn occupied_by(&self, team: Team) -> u64 {
+        self.occupancy[team as usize]
+    }
+
+    /// gets the squares holding a team's pieces of some kind, as a bitboard
+    pub fn pieces(&self, team: Team, chessman: Chessman) -> u64 {
+        self.pieces[slot(team, chessman)]
+    }
+
+    /// gets the pieces after a move is made, which is all that's needed to see if it leaves the king in check
+    fn pieces_after(&self, m: Move) -> [u64; NUM_TEAMS * NUM_CHESSMEN] {
+        let mut pieces = self.pieces;
+        let team = self.side_to_move;
+
+        if let Some((_, chessman)) = m.piece().data() {
+            pieces[slot(team, chessman)] &= !bit(m.origin());
+
+            let placed = m.kind().promotion_piece().unwrap_or(chessman);
+            pieces[slot(team, placed)] |= bit(m.target());
+        }
+
+        if let Some((victim_team, victim)) = m.capture().data() {
+            let square = match m.kind() {
+                // the pawn being taken sits beside the capturing one, on the target's file
+                MoveKind::EPCapture => Coordinate::new_unchecked(m.origin().rank() * BOARD_LENGTH as u8 + m.target().file()),
+                _ => m.target(),
+            };
+            pieces[slot(victim_team, victim)] &= !bit(square);
+        }
+
+        let home = m.origin().rank() * BOARD_LENGTH as u8;
+        let rook_move = match m.kind() {
+            MoveKind::KingCastle => Some((home + 7, home + 5)),
+            MoveKind::QueenCastle => Some((home, home + 3)),
+            _ => None,
+        };
+        if let Some((from, to)) = rook_move {
+            pieces[slot(team, Chessman::Rook)] ^= (1 << from) | (1 << to);
+        }
+
+        pieces
+    }
+
+    /// adds a quiet move or capture onto each target square, the targets can't hold friendly pieces
+    fn push_targets(&self, moves: &mut Vec<Move>, from: Coordinate, targets: u64) {
+        let piece = self.get_tile(from);
+
+        for target in squares(targets) {
+            let capture = self.get_tile(target);
+            let kind = if capture.data().is_some() { MoveKind::Capture } else { MoveKind::QuietMove };
+            moves.push(Move::new(kind, from, target, piece, capture));
+        }
+    }
+
+    /// adds the pseudo-legal moves of a pawn
+    fn push_pawn_moves(&self, moves: &mut Vec<Move>, from: Coordinate, team: Team) {
+        let (forward, start_rank, last_rank) = match team {
+            Team::White => (1, 1, BOARD_LENGTH as u8 - 1),
+            Team::Black => (-1, BOARD_LENGTH as u8 - 2, 0),
+        };
+        let occupied = self.occupied();
+        let piece = Piece::new(Some((team, Chessman::Pawn)));
+
+        if let Some(target) = from.offset(forward, 0).filter(|&t| occupied & bit(t) == 0) {
+            if target.rank() == last_rank {
+                for kind in PROMOTIONS {
+                    moves.push(Move::new(kind, from, target, piece, Piece::new(None)));
+                }
+            } else {
+                moves.push(Move::new(MoveKind::QuietMove, from, target, piece, Piece::new(None)));
+
+                let double = target.offset(forward, 0).filter(|&t| occupied & bit(t) == 0);
+                if let Some(double) = double.filter(|_| from.rank() == start_rank) {
+                    moves.push(Move::new(MoveKind::DoublePawnPush, from, double, piece, Piece::new(None)));
+                }
+            }
+        }
+
+        let attacks = pawn_attacks(from, team);
+        for target in squares(attacks & self.occupancy[team.opponent() as usize]) {
+            let capture = self.get_tile(target);
+
+            if target.rank() == last_rank {
+                for kind in PROMOTION_CAPTURES {
+                    moves.push(Move::new(kind, from, target, piece, capture));
+                }
+            } else {
+                moves.push(Move::new(MoveKind::Capture, from, target, piece, capture));
+            }
+        }
+
+        if let Some(target) = self.en_passant.filter(|&t| attacks & bit(t) != 0 && occupied & bit(t) == 0) {
+            let passed = Coordinate::new_unchecked(from.rank() * BOARD_LENGTH as u8 + target.file());
+            let enemy_pawn = Piece::new(Some((team.opponent(), Chessman::Pawn)));
+
+            if self.get_tile(passed) == enemy_pawn {
+                moves.push(Move::new(MoveKind::EPCapture, from, target, piece, enemy_pawn));
+            }
+        }
+    }
+
+    /// adds the castling moves whose rights are held and whose squares between the king and rook are empty
+    fn push_castles(&self, moves: &mut Vec<Move>, team: Team) {
+        let home = match team {
+            Team::White => 0,
+            Team::Black => (BOARD_LENGTH as u8 - 1) * BOARD_LENGTH as u8,
+        };
+        let king = Coordinate::new_unchecked(home + 4);
+        if self.pieces[slot(team, Chessman::King)] & bit(king) == 0 {
+            return;
+        }
+
+        let occupied = self.occupied();
+        let rooks = self.pieces[slot(team, Chessman::Rook)];
+
+        // (side, kind, rook file, files that must be empty, king target file)
+        let castles = [
+            (CastleSide::KingSide, MoveKind::KingCastle, 7, 0b0110_0000_u64, 6),
+            (CastleSide::QueenSide, MoveKind::QueenCastle, 0, 0b0000_1110_u64, 2),
+        ];
+
+        for (side, kind, rook_file, between, target_file) in castles {
+            if self.can_castle(team, side) && rooks & (1 << (home + rook_file)) != 0 && occupied & (between << home) == 0 {
+                let target = Coordinate::new_unchecked(home + target_file);
+                moves.push(Move::new(kind, king, target, Piece::new(Some((team, Chessman::King))), Piece::new(None)));
+            }
+        }
+    }
+
+    /// Generates the legal moves for the side to move, in the same way as `MailboxBoard::legal_moves`
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mover = self.side_to_move;
+        let in_check = |pieces: &[u64; NUM_TEAMS * NUM_CHESSMEN]| {
+            squares(pieces[slot(mover, Chessman::King)]).any(|king| attacked(pieces, king, mover.opponent()))
+        };
+
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|&m| {
+                if matches!(m.kind(), MoveKind::KingCastle | MoveKind::QueenCastle) {
+                    let passed = Coordinate::new_unchecked((m.origin().index() + m.target().index()) / 2);
+                    if attacked(&self.pieces, m.origin(), mover.opponent()) || attacked(&self.pieces, passed, mover.opponent()) {
+                        return false;
+                    }
+                }
+
+                !in_check(&self.pieces_after(m))
+            })
+            .collect()
+    }
+
+    /// Generates the pseudo-legal moves for the side to move, see `movegen::team_moves`
+    fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let team = self.side_to_move;
+        let own = self.occupancy[team as usize];
+        let occupied = self.occupied();
+        let mut moves = Vec::new();
+
+        for chessman in Chessman::all() {
+            for from in squares(self.pieces[slot(team, chessman)]) {
+                let attacks = match chessman {
+                    Chessman::King => KING_ATTACKS[from.index() as usize],
+                    Chessman::Queen => rook_attacks(from, occupied) | bishop_attacks(from, occupied),
+                    Chessman::Bishop => bishop_attacks(from, occupied),
+                    Chessman::Knight => KNIGHT_ATTACKS[from.index() as usize],
+                    Chessman::Rook => rook_attacks(from, occupied),
+                    Chessman::Pawn => {
+                        self.push_pawn_moves(&mut moves, from, team);
+                        continue;
+                    }
+                };
+
+                self.push_targets(&mut moves, from, attacks & !own);
+            }
+        }
+
+        self.push_castles(&mut moves, team);
+        moves
+    }
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        BitBoard::empty()
+    }
+}
+
+impl From<&MailboxBoard> for BitBoard {
+    fn from(board: &MailboxBoard) -> Self {
+        let mut bitboard = BitBoard::empty();
+
+        for square in Coordinate::all() {
+            bitboard.set_tile(square, board.get_tile(square));
+        }
+
+        bitboard.side_to_move = board.side_to_move();
+        bitboard.en_passant = board.en_passant();
+        for team in Team::all() {
+            for side in [CastleSide::KingSide, CastleSide::QueenSide] {
+                if board.can_castle(team, side) {
+                    bitboard.castling |= castle_bit(team, side);
+                }
+            }
+        }
+
+        bitboard
+    }
+}
+
+impl Board for BitBoard {
+    fn set_tile(&mut self, coord: Coordinate, piece: Piece) {
+        let mask = !bit(coord);
+        for bits in self.pieces.iter_mut().chain(self.occupancy.iter_mut()) {
+            *bits &= mask;
+        }
+
+        if let Some((team, chessman)) = piece.data() {
+            self.pieces[slot(team, chessman)] |= bit(coord);
+            self.occupancy[team as usize] |= bit(coord);
+        }
+    }
+
+    fn clear_tile(&mut self, coord: Coordinate) {
+        self.set_tile(coord, Piece::new(None));
+    }
+
+    /// Generates the pseudo-legal moves for the side to move, like `MailboxBoard`'s, see
+    /// [`legal_moves`](BitBoard::legal_moves) for the legal ones
+    fn get_moves(&self) -> Vec<Move> {
+        self.pseudo_legal_moves()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// gets the moves sorted so move lists from different generators can be compared
+    fn sorted(mut moves: Vec<Move>) -> Vec<Move> {
+        moves.sort_by_key(|m| (m.to_uci(), m.kind() as u8));
+        moves
+    }
+
+    #[test]
+    fn test_from_fen_matches_mailbox() {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let bitboard = BitBoard::from_fen(fen).unwrap();
+        let mailbox = MailboxBoard::from_fen(fen).unwrap();
+
+        for square in Coordinate::all() {
+            assert_eq!(bitboard.get_tile(square), mailbox.get_tile(square), "{}", square);
+        }
+        assert_eq!(bitboard.side_to_move(), Team::White);
+        assert!(bitboard.can_castle(Team::Black, CastleSide::QueenSide));
+        assert_eq!(bitboard.en_passant(), None);
+    }
+
+    #[test]
+    fn test_occupancy() {
+        let board = BitBoard::from(&MailboxBoard::starting_position());
+
+        assert_eq!(board.occupied().count_ones(), 32);
+        assert_eq!(board.occupied(), board.occupied_by(Team::White) | board.occupied_by(Team::Black));
+        assert_eq!(board.occupied_by(Team::White), 0xffff);
+        assert_eq!(board.occupied_by(Team::Black), 0xffff << 48);
+
+        assert_eq!(board.pieces(Team::White, Chessman::Pawn), 0xff00);
+        assert_eq!(board.pieces(Team::Black, Chessman::King), 1 << 60);
+        assert_eq!(board.pieces(Team::Black, Chessman::Rook), 1 << 56 | 1 << 63);
+        assert_eq!(BitBoard::empty().occupied(), 0);
+    }
+
+    #[test]
+    fn test_set_and_clear_tile() {
+        let mut board = BitBoard::empty();
+        let square = Coordinate::from_algebraic("d4").unwrap();
+        let knight = Piece::new(Some((Team::White, Chessman::Knight)));
+        let pawn = Piece::new(Some((Team::Black, Chessman::Pawn)));
+
+        board.set_tile(square, knight);
+        assert_eq!(board.get_tile(square), knight);
+
+        // replacing a piece clears its old bits
+        board.set_tile(square, pawn);
+        assert_eq!(board.get_tile(square), pawn);
+        assert_eq!(board.pieces[slot(Team::White, Chessman::Knight)], 0);
+        assert_eq!(board.occupancy, [0, bit(square)]);
+
+        board.clear_tile(square);
+        assert_eq!(board.get_tile(square), Piece::new(None));
+        assert_eq!(board.pieces, [0; NUM_TEAMS * NUM_CHESSMEN]);
+        assert_eq!(board.occupancy, [0; NUM_TEAMS]);
+    }
+
+    #[test]
+    fn test_clone() {
+        let board = BitBoard::from(&MailboxBoard::starting_position());
+        let mut copy = board.clone();
+        let e2 = Coordinate::from_algebraic("e2").unwrap();
+        copy.clear_tile(e2);
+
+        assert_eq!(copy.get_tile(e2), Piece::new(None));
+        assert_eq!(board.get_tile(e2), Piece::new(Some((Team::White, Chessman::Pawn))));
+        assert_eq!(board.occupied().count_ones(), 32);
+    }
+
+    #[test]
+    fn test_get_moves_matches_mailbox() {
+        /// compares the pseudo-legal and legal moves at every position down to the depth
+        fn compare(board: &mut MailboxBoard, depth: u8) {
+            let bitboard = BitBoard::from(&*board);
+            assert_eq!(sorted(bitboard.get_moves()), sorted(board.get_moves()), "{}", board.to_fen());
+
+            let moves = board.legal_moves();
+            assert_eq!(sorted(bitboard.legal_moves()), sorted(moves.clone()), "{}", board.to_fen());
+
+            if depth > 0 {
+                for m in moves {
+                    let undo = board.make_move(m);
+                    compare(board, depth - 1);
+                    board.unmake_move(undo);
+                }
+            }
+        }
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ] {
+            compare(&mut MailboxBoard::from_fen(fen).unwrap(), 2);
+        }
+    }
+}
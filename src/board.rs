@@ -1,7 +1,12 @@
 // use std::mem::{variant_count}; // from nightly, but don't feel like setting this up right now.
 
-use std::convert::TryFrom;
-use crate::board;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use crate::movegen;
+use crate::zobrist::ZOBRIST;
+use core::str::FromStr;
 
 /// The primary trait used to represent current board state
 pub trait Board {
@@ -17,6 +22,7 @@ pub trait Board {
 
 /// Kinds of available moves
 #[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MoveKind {
     QuietMove,
     DoublePawnPush,
@@ -34,15 +40,107 @@ pub enum MoveKind {
     QueenPromotionCapture,
 }
 
+impl MoveKind {
+    /// gets the chessman a move of this kind promotes to, if it's a promotion
+    pub fn promotion_piece(self) -> Option<Chessman> {
+        match self {
+            MoveKind::KnightPromotion | MoveKind::KnightPromotionCapture => Some(Chessman::Knight),
+            MoveKind::BishopPromotion | MoveKind::BishopPromotionCapture => Some(Chessman::Bishop),
+            MoveKind::RookPromotion | MoveKind::RookPromotionCapture => Some(Chessman::Rook),
+            MoveKind::QueenPromotion | MoveKind::QueenPromotionCapture => Some(Chessman::Queen),
+            _ => None,
+        }
+    }
+
+    /// checks whether a move of this kind takes a piece, which includes en passant and capturing promotions
+    pub fn is_capture(self) -> bool {
+        matches!(
+            self,
+            MoveKind::Capture
+                | MoveKind::EPCapture
+                | MoveKind::KnightPromotionCapture
+                | MoveKind::BishopPromotionCapture
+                | MoveKind::RookPromotionCapture
+                | MoveKind::QueenPromotionCapture
+        )
+    }
+
+    /// checks whether a move of this kind promotes a pawn
+    pub fn is_promotion(self) -> bool {
+        self.promotion_piece().is_some()
+    }
+}
+
 /// Information for a move
+///
+/// This packs into 4 bytes, with the moving and captured pieces sharing one, so move lists stay small.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Move {
     kind: MoveKind,
 
     origin: Coordinate,
     target: Coordinate,
 
-    piece: Piece, //todo: use both halves of a u8 to store these 2 instead?
-    capture: Piece,
+    /// the moving piece in the low half and the captured one in the high half, see `Piece::to_nibble`
+    pieces: u8,
+}
+
+impl Move {
+    /// Creates a new move
+    pub fn new(kind: MoveKind, origin: Coordinate, target: Coordinate, piece: Piece, capture: Piece) -> Self {
+        Move { kind, origin, target, pieces: piece.to_nibble() | capture.to_nibble() << 4 }
+    }
+
+    /// gets the kind of move
+    pub fn kind(&self) -> MoveKind {
+        self.kind
+    }
+
+    /// gets the tile the piece moves from
+    pub fn origin(&self) -> Coordinate {
+        self.origin
+    }
+
+    /// gets the tile the piece moves to
+    pub fn target(&self) -> Coordinate {
+        self.target
+    }
+
+    /// gets the piece being moved
+    pub fn piece(&self) -> Piece {
+        Piece::from_nibble(self.pieces & 0xf)
+    }
+
+    /// gets the piece being captured, unoccupied if there isn't one
+    pub fn capture(&self) -> Piece {
+        Piece::from_nibble(self.pieces >> 4)
+    }
+
+    /// gets the UCI letter of the piece the move promotes to, if it's a promotion
+    pub(crate) fn promotion_letter(&self) -> Option<char> {
+        self.kind.promotion_piece().map(Chessman::to_char)
+    }
+
+    /// Writes the move in UCI long algebraic notation, like `e2e4` or `e7e8q`
+    #[cfg(feature = "std")]
+    pub fn to_uci(&self) -> String {
+        match self.promotion_letter() {
+            Some(letter) => format!("{}{}{}", self.origin, self.target, letter),
+            None => format!("{}{}", self.origin, self.target),
+        }
+    }
+}
+
+impl fmt::Debug for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Move")
+            .field("kind", &self.kind)
+            .field("origin", &self.origin)
+            .field("target", &self.target)
+            .field("piece", &self.piece())
+            .field("capture", &self.capture())
+            .finish()
+    }
 }
 
 /// Errors for the board
@@ -50,13 +148,94 @@ pub struct Move {
 pub enum Error {
     OutOfBoundsAxis,
     OutOfBoundsIndex,
+    InvalidAlgebraic,
+    InvalidPieceCharacter(char),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfBoundsAxis => write!(f, "coordinate axis out of bounds"),
+            Error::OutOfBoundsIndex => write!(f, "coordinate index out of bounds"),
+            Error::InvalidAlgebraic => write!(f, "invalid algebraic square"),
+            Error::InvalidPieceCharacter(c) => write!(f, "invalid piece character {:?}", c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Errors from parsing a move against a board
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoveParseError {
+    /// the move isn't 4 or 5 characters long
+    InvalidLength,
+    /// one of the squares isn't valid algebraic notation
+    InvalidSquare(Error),
+    /// the promotion letter isn't one of `n`, `b`, `r`, or `q`
+    InvalidPromotion(char),
+    /// a pawn reached the last rank without saying what to promote to
+    MissingPromotion,
+    /// a promotion was given for a move that doesn't promote
+    UnexpectedPromotion,
+    /// there's no piece on the origin square
+    EmptyOrigin,
+    /// the piece on the origin square isn't the side to move's
+    WrongTeam,
+    /// the target square holds one of the mover's own pieces
+    FriendlyTarget,
+    /// the move is well formed but isn't one of the legal moves in the position
+    IllegalMove,
+}
+
+/// Reasons a position can't come up in a real game
+#[derive(Debug, PartialEq, Eq)]
+pub enum PositionError {
+    /// the team has no king
+    MissingKing(Team),
+    /// the team has more than one king
+    TooManyKings(Team),
+    /// the team has more than 8 pawns
+    TooManyPawns(Team),
+    /// there's a pawn on rank 1 or 8, where pawns can never stand
+    PawnOnBackRank(Coordinate),
+    /// the side that just moved is in check, so its king could be taken
+    OpponentInCheck,
 }
 
 /// The teams that are playing a game of chess
 #[repr(u8)]
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Team { White = 0, Black = 1 }
 
+impl Team {
+    /// gets every team, White first
+    pub const fn all() -> [Team; NUM_TEAMS] {
+        [Team::White, Team::Black]
+    }
+
+    /// gets the team playing against this one
+    pub fn opponent(self) -> Team {
+        match self {
+            Team::White => Team::Black,
+            Team::Black => Team::White,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Team {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Team::White => write!(f, "White"),
+            Team::Black => write!(f, "Black"),
+        }
+    }
+}
+
 /// bit mask to get the team bit
 const MASK_TEAM: u8 = 0b1000;
 
@@ -64,22 +243,90 @@ const MASK_TEAM: u8 = 0b1000;
 const SHIFT_TEAM: u8 = 3;
 
 /// Number of teams
-const NUM_TEAMS: usize = 2;
+pub const NUM_TEAMS: usize = 2;
 // const NUM_TEAMS: usize = variant_count::<Team>();
 
 /// The kinds of valid pieces on the chess board
 #[repr(u8)]
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Chessman { King = 0, Queen = 1, Bishop = 2, Knight = 3, Rook = 4, Pawn = 5 }
 
+impl Chessman {
+    /// gets every chessman, in the order of their discriminants
+    pub const fn all() -> [Chessman; NUM_CHESSMEN] {
+        [Chessman::King, Chessman::Queen, Chessman::Bishop, Chessman::Knight, Chessman::Rook, Chessman::Pawn]
+    }
+
+    /// gets the lowercase FEN letter for the chessman, like `n` for a knight
+    pub fn to_char(self) -> char {
+        match self {
+            Chessman::King => 'k',
+            Chessman::Queen => 'q',
+            Chessman::Bishop => 'b',
+            Chessman::Knight => 'n',
+            Chessman::Rook => 'r',
+            Chessman::Pawn => 'p',
+        }
+    }
+
+    /// gets the chessman for a FEN letter in either case, since the case only says which team it is
+    pub fn from_char(letter: char) -> Option<Chessman> {
+        match letter.to_ascii_lowercase() {
+            'k' => Some(Chessman::King),
+            'q' => Some(Chessman::Queen),
+            'b' => Some(Chessman::Bishop),
+            'n' => Some(Chessman::Knight),
+            'r' => Some(Chessman::Rook),
+            'p' => Some(Chessman::Pawn),
+            _ => None,
+        }
+    }
+
+    /// Gets the chessman's worth in centipawns
+    ///
+    /// Kings are worth 0 since they're never traded, so this isn't the declaration order. Compare values
+    /// rather than chessmen to rank them by worth.
+    pub fn value(self) -> i32 {
+        match self {
+            Chessman::King => 0,
+            Chessman::Queen => 900,
+            Chessman::Bishop => 330,
+            Chessman::Knight => 320,
+            Chessman::Rook => 500,
+            Chessman::Pawn => 100,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Chessman {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Chessman::King => "King",
+            Chessman::Queen => "Queen",
+            Chessman::Bishop => "Bishop",
+            Chessman::Knight => "Knight",
+            Chessman::Rook => "Rook",
+            Chessman::Pawn => "Pawn",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Bit mask for the chessman
 const MASK_CHESSMAN: u8 = 0b111;
 
 /// Number of kinds of pieces
-const NUM_CHESSMEN: usize = 6;
+pub const NUM_CHESSMEN: usize = 6;
 // const NUM_PIECE_KINDS: usize = variant_count::<Team>();
 
 /// struct to represent the piece information
+///
+/// With the `serde` feature this serializes as its `data`, so only valid pieces can be read back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Option<(Team, Chessman)>", into = "Option<(Team, Chessman)>"))]
 pub struct Piece {
     value: u8,
 }
@@ -119,30 +366,131 @@ impl Piece {
             Some((team, chessman))
         }
     }
+
+    /// packs the piece into 4 bits, which the occupied values already fit in, using all ones for an empty tile
+    fn to_nibble(self) -> u8 {
+        if self.value & Piece::MASK_UNOCCUPIED != 0 {
+            0xf
+        } else {
+            self.value
+        }
+    }
+
+    /// unpacks a piece packed by `to_nibble`
+    fn from_nibble(nibble: u8) -> Piece {
+        if nibble == 0xf {
+            Piece::new(None)
+        } else {
+            Piece { value: nibble }
+        }
+    }
+
+    /// gets the FEN letter for the piece, uppercase for White and lowercase for Black, or None for an empty tile
+    pub fn to_fen_char(self) -> Option<char> {
+        self.data().map(|(team, chessman)| match team {
+            Team::White => chessman.to_char().to_ascii_uppercase(),
+            Team::Black => chessman.to_char(),
+        })
+    }
+}
+
+impl TryFrom<char> for Piece {
+    type Error = Error;
+
+    /// reads a FEN letter, where uppercase is White and lowercase is Black
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        let team = if c.is_ascii_uppercase() { Team::White } else { Team::Black };
+        let chessman = Chessman::from_char(c).ok_or(Error::InvalidPieceCharacter(c))?;
+        Ok(Piece::new(Some((team, chessman))))
+    }
+}
+
+impl From<Option<(Team, Chessman)>> for Piece {
+    fn from(value: Option<(Team, Chessman)>) -> Self {
+        Piece::new(value)
+    }
+}
+
+impl From<Piece> for Option<(Team, Chessman)> {
+    fn from(piece: Piece) -> Self {
+        piece.data()
+    }
 }
 
 impl Default for Piece {
+    /// an unoccupied tile, so it compares equal to `Piece::new(None)`
     fn default() -> Self {
-        Piece { value: 0 }
+        Piece::new(None)
     }
 }
 
 /// represents coordinates, should only ever be 0 <= value < 64
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+///
+/// With the `serde` feature this serializes as its index, which is checked when read back.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8", into = "u8"))]
 pub struct Coordinate {
     value: u8,
 }
 
 impl Coordinate {
+    /// Creates a coordinate from a raw index without checking it
+    ///
+    /// The value must be less than `NUM_TILES`, use `TryFrom` when that isn't already known.
+    pub fn new_unchecked(value: u8) -> Coordinate {
+        debug_assert!(value < NUM_TILES as u8, "coordinate index out of range");
+        Coordinate { value }
+    }
+
+    /// gets the raw index of the coordinate
+    pub fn index(&self) -> u8 {
+        self.value
+    }
+
     /// gets the rank of the coordinate
-    fn rank(&self) -> u8 {
+    pub fn rank(&self) -> u8 {
         self.value / BOARD_LENGTH as u8
     }
 
     /// gets the file of the coordinate
-    fn file(&self) -> u8 {
+    pub fn file(&self) -> u8 {
         self.value % BOARD_LENGTH as u8
     }
+
+    /// Shifts the coordinate by a rank and file offset, or None if that leaves the board
+    ///
+    /// The rank and file are shifted separately, so moving off the side of the board never wraps around onto
+    /// the other side the way adding to the index would.
+    pub fn offset(self, d_rank: i8, d_file: i8) -> Option<Coordinate> {
+        let rank = u8::try_from(self.rank() as i8 + d_rank).ok()?;
+        let file = u8::try_from(self.file() as i8 + d_file).ok()?;
+
+        Coordinate::try_from((rank, file)).ok()
+    }
+
+    /// gets the square on the same file but the opposite rank, so a1 becomes a8
+    pub fn flip_vertical(self) -> Coordinate {
+        Coordinate { value: self.value ^ (NUM_TILES - BOARD_LENGTH) as u8 }
+    }
+
+    /// checks whether this is a light square, like h1, rather than a dark one like a1
+    pub fn is_light(self) -> bool {
+        (self.rank() + self.file()) % 2 == 1
+    }
+
+    /// Iterates over every square on the board in index order, from a1 to h8
+    pub fn all() -> impl Iterator<Item = Coordinate> {
+        (0..NUM_TILES as u8).map(|value| Coordinate { value })
+    }
+
+    /// parses a square in algebraic notation, like `e4`
+    pub fn from_algebraic(square: &str) -> Result<Self, Error> {
+        match square.as_bytes() {
+            &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Coordinate::try_from((rank - b'1', file - b'a')),
+            _ => Err(Error::InvalidAlgebraic),
+        }
+    }
 }
 
 /// the length of the chess board
@@ -174,64 +522,1507 @@ impl TryFrom<u8> for Coordinate {
     }
 }
 
+impl From<Coordinate> for u8 {
+    fn from(coord: Coordinate) -> Self {
+        coord.value
+    }
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl FromStr for Coordinate {
+    type Err = Error;
 
-    #[test]
-    fn test_piece() {
-        for team in [Team::White, Team::Black] {
-            for chessman in [
-                Chessman::King,
-                Chessman::Pawn,
-                Chessman::Knight,
-                Chessman::Bishop,
-                Chessman::Queen,
-                Chessman::Rook] {
-                let piece = Piece::new(Some((team, chessman)));
+    fn from_str(square: &str) -> Result<Self, Self::Err> {
+        Coordinate::from_algebraic(square)
+    }
+}
 
-                assert_eq!(piece.data(), Some((team, chessman)));
+#[cfg(feature = "std")]
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file()) as char, self.rank() + 1)
+    }
+}
+
+/// The sides of the board a king can castle toward
+#[repr(u8)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum CastleSide { KingSide = 0, QueenSide = 1 }
+
+/// castling rights bit for White castling kingside
+const CASTLE_WHITE_KING: u8 = 0b0001;
+
+/// castling rights bit for White castling queenside
+const CASTLE_WHITE_QUEEN: u8 = 0b0010;
+
+/// castling rights bit for Black castling kingside
+const CASTLE_BLACK_KING: u8 = 0b0100;
+
+/// castling rights bit for Black castling queenside
+const CASTLE_BLACK_QUEEN: u8 = 0b1000;
+
+/// gets the castling rights bit for a team and side
+pub(crate) fn castle_bit(team: Team, side: CastleSide) -> u8 {
+    match (team, side) {
+        (Team::White, CastleSide::KingSide) => CASTLE_WHITE_KING,
+        (Team::White, CastleSide::QueenSide) => CASTLE_WHITE_QUEEN,
+        (Team::Black, CastleSide::KingSide) => CASTLE_BLACK_KING,
+        (Team::Black, CastleSide::QueenSide) => CASTLE_BLACK_QUEEN,
+    }
+}
+
+/// Everything needed to take back a move made with `MailboxBoard::make_move`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    m: Move,
+    castling: u8,
+    en_passant: Option<Coordinate>,
+    side_to_move: Team,
+    repetition_start: usize,
+    halfmove_clock: u16,
+}
+
+/// A board stored as a plain array of tiles, indexed by `Coordinate`
+///
+/// Two boards are equal when they hold the same position: the same pieces on the same tiles, side to move,
+/// castling rights and en passant square. How they got there, and the halfmove clock, don't matter.
+#[derive(Clone)]
+pub struct MailboxBoard {
+    tiles: [Piece; NUM_TILES],
+    side_to_move: Team,
+    castling: u8,
+    en_passant: Option<Coordinate>,
+    hash: u64,
+
+    /// hashes of the positions before each move made, for spotting repetitions
+    history: Vec<u64>,
+    /// where in `history` the positions that can still repeat start, after the last capture or pawn move
+    repetition_start: usize,
+    /// moves since the last capture or pawn move, for the fifty-move rule
+    halfmove_clock: u16,
+    /// the number of the move being played, starting at 1 and going up after each of Black's moves
+    fullmove_number: u16,
+}
+
+impl MailboxBoard {
+    /// Creates a board with no pieces on it
+    pub fn empty() -> Self {
+        MailboxBoard {
+            tiles: [Piece::new(None); NUM_TILES],
+            side_to_move: Team::White,
+            castling: 0,
+            en_passant: None,
+            hash: 0,
+            history: Vec::new(),
+            repetition_start: 0,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    /// Creates a board set up for the start of a game
+    pub fn starting_position() -> Self {
+        const BACK_RANK: [Chessman; BOARD_LENGTH] = [
+            Chessman::Rook,
+            Chessman::Knight,
+            Chessman::Bishop,
+            Chessman::Queen,
+            Chessman::King,
+            Chessman::Bishop,
+            Chessman::Knight,
+            Chessman::Rook,
+        ];
+
+        let mut board = MailboxBoard::empty();
+
+        for (file, chessman) in BACK_RANK.into_iter().enumerate() {
+            let file = file as u8;
+            for (team, back_rank, pawn_rank) in [(Team::White, 0, 1), (Team::Black, 7, 6)] {
+                let back = Coordinate { value: back_rank * BOARD_LENGTH as u8 + file };
+                let front = Coordinate { value: pawn_rank * BOARD_LENGTH as u8 + file };
+
+                board.set_tile(back, Piece::new(Some((team, chessman))));
+                board.set_tile(front, Piece::new(Some((team, Chessman::Pawn))));
             }
         }
 
-        let piece = Piece::new(None);
-        assert_eq!(piece.data(), None);
+        board.set_castling(CASTLE_WHITE_KING | CASTLE_WHITE_QUEEN | CASTLE_BLACK_KING | CASTLE_BLACK_QUEEN);
+        board
     }
 
-    #[test]
-    fn test_coordinate() {
-        for file in 0..=BOARD_LENGTH {
-            for rank in 0..=BOARD_LENGTH {
-                let file = file as u8;
-                let rank = rank as u8;
+    /// gets the piece on a tile
+    pub fn get_tile(&self, coord: Coordinate) -> Piece {
+        self.tiles[coord.value as usize]
+    }
 
-                let test_coord = Coordinate::try_from((rank, file));
+    /// Sets the piece on each of the tiles given, in order, so a later placement on a tile replaces an earlier one
+    pub fn set_tiles(&mut self, placements: &[(Coordinate, Piece)]) {
+        for &(coord, piece) in placements {
+            self.set_tile(coord, piece);
+        }
+    }
 
-                if file < BOARD_LENGTH as u8 && rank < BOARD_LENGTH as u8 {
-                    let test_coord = test_coord.expect("try_from failed");
-                    assert_eq!(test_coord.rank(), rank);
-                    assert_eq!(test_coord.file(), file);
-                } else {
-                    assert_eq!(test_coord, Err(Error::OutOfBoundsAxis));
+    /// gets the team whose turn it is
+    pub fn side_to_move(&self) -> Team {
+        self.side_to_move
+    }
+
+    /// sets the team whose turn it is
+    pub fn set_side_to_move(&mut self, team: Team) {
+        if team != self.side_to_move {
+            self.hash ^= ZOBRIST.side_to_move();
+        }
+        self.side_to_move = team;
+    }
+
+    /// gets the square a pawn skipped over with a double push last move, if any
+    pub fn en_passant(&self) -> Option<Coordinate> {
+        self.en_passant
+    }
+
+    /// sets the square that can be captured en passant
+    pub fn set_en_passant(&mut self, square: Option<Coordinate>) {
+        for square in [self.en_passant, square].into_iter().flatten() {
+            self.hash ^= ZOBRIST.en_passant(square);
+        }
+        self.en_passant = square;
+    }
+
+    /// gets the Zobrist hash of the position, which is kept up to date as the board changes
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// finds the square of a team's king, if it has one
+    pub fn king_square(&self, team: Team) -> Option<Coordinate> {
+        let king = Piece::new(Some((team, Chessman::King)));
+
+        Coordinate::all().find(|&coord| self.get_tile(coord) == king)
+    }
+
+    /// counts how many of a kind of piece a team has on the board
+    pub fn count(&self, team: Team, chessman: Chessman) -> u8 {
+        let piece = Piece::new(Some((team, chessman)));
+        Coordinate::all().filter(|&coord| self.get_tile(coord) == piece).count() as u8
+    }
+
+    /// Counts the pieces a team has on the board, indexed by `Chessman`
+    pub fn material_count(&self, team: Team) -> [u8; NUM_CHESSMEN] {
+        let mut counts = [0; NUM_CHESSMEN];
+        for coord in Coordinate::all() {
+            if let Some((owner, chessman)) = self.get_tile(coord).data() {
+                if owner == team {
+                    counts[chessman as usize] += 1;
                 }
+            }
+        }
+        counts
+    }
 
-                let index = (rank * BOARD_LENGTH as u8) | file;
-                let test_coord = Coordinate::try_from(index);
+    /// checks whether the current position has come up before, since the last capture or pawn move
+    pub fn is_repetition(&self) -> bool {
+        self.history[self.repetition_start..].contains(&self.hash)
+    }
 
-                if index < NUM_TILES as u8 {
-                    let test_coord = test_coord.expect("try_from failed");
-                    // just comparing to rank/file doesnt work because adding too high file to not too big rank might not be out of range
-                    assert_eq!(test_coord.rank(), index / BOARD_LENGTH as u8);
-                    assert_eq!(test_coord.file(), index % BOARD_LENGTH as u8);
-                } else {
-                    assert_eq!(test_coord, Err(Error::OutOfBoundsIndex))
+    /// Checks whether the current position has come up three times
+    ///
+    /// Only positions since the last capture or pawn move are considered, since nothing before that can repeat.
+    pub fn is_threefold_repetition(&self) -> bool {
+        let repeats = self.history[self.repetition_start..].iter().filter(|&&hash| hash == self.hash).count();
+        repeats + 1 >= 3
+    }
+
+    /// gets the number of moves since the last capture or pawn move
+    pub fn halfmove_clock(&self) -> u16 {
+        self.halfmove_clock
+    }
+
+    /// sets the number of moves since the last capture or pawn move
+    pub fn set_halfmove_clock(&mut self, halfmove_clock: u16) {
+        self.halfmove_clock = halfmove_clock;
+    }
+
+    /// gets the number of the move being played, which starts at 1 and goes up after each of Black's moves
+    pub fn fullmove_number(&self) -> u16 {
+        self.fullmove_number
+    }
+
+    /// sets the number of the move being played
+    pub fn set_fullmove_number(&mut self, fullmove_number: u16) {
+        self.fullmove_number = fullmove_number;
+    }
+
+    /// Checks whether fifty moves by each side have passed without a capture or pawn move
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// Checks for the dead positions where neither side has enough material to checkmate
+    ///
+    /// These are king vs king, king and a minor piece vs king, and king and bishop vs king and bishop with both
+    /// bishops on the same color squares.
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minors = Vec::new();
+
+        for coord in Coordinate::all() {
+            match self.get_tile(coord).data() {
+                None | Some((_, Chessman::King)) => {}
+                Some((team, chessman @ (Chessman::Bishop | Chessman::Knight))) => {
+                    minors.push((team, chessman, coord.is_light()));
+                }
+                Some(_) => return false,
+            }
+        }
+
+        match minors[..] {
+            [] | [_] => true,
+            [(first, Chessman::Bishop, first_color), (second, Chessman::Bishop, second_color)] => {
+                first != second && first_color == second_color
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks that the position could come up in a real game
+    ///
+    /// Each team needs exactly one king and at most 8 pawns, no pawns can be on the first or last rank, and the
+    /// side that isn't moving can't be in check. Positions that are only unreachable because of how many
+    /// promoted pieces there are, or how the castling rights and en passant square are set, aren't caught.
+    pub fn validate(&self) -> Result<(), PositionError> {
+        for coord in Coordinate::all() {
+            let is_back_rank = coord.rank() == 0 || coord.rank() == BOARD_LENGTH as u8 - 1;
+            if is_back_rank && matches!(self.get_tile(coord).data(), Some((_, Chessman::Pawn))) {
+                return Err(PositionError::PawnOnBackRank(coord));
+            }
+        }
+
+        for team in Team::all() {
+            let counts = self.material_count(team);
+            match counts[Chessman::King as usize] {
+                0 => return Err(PositionError::MissingKing(team)),
+                1 => {}
+                _ => return Err(PositionError::TooManyKings(team)),
+            }
+            if counts[Chessman::Pawn as usize] > 8 {
+                return Err(PositionError::TooManyPawns(team));
+            }
+        }
+
+        if self.in_check(self.side_to_move.opponent()) {
+            return Err(PositionError::OpponentInCheck);
+        }
+
+        Ok(())
+    }
+
+    /// checks whether a team still has the right to castle toward a side
+    ///
+    /// This only tracks the right itself, not whether castling is possible right now.
+    pub fn can_castle(&self, team: Team, side: CastleSide) -> bool {
+        self.castling & castle_bit(team, side) != 0
+    }
+
+    /// gives a team the right to castle toward a side
+    pub fn grant_castle(&mut self, team: Team, side: CastleSide) {
+        self.set_castling(self.castling | castle_bit(team, side));
+    }
+
+    /// takes away a team's right to castle toward a side
+    pub fn revoke_castle(&mut self, team: Team, side: CastleSide) {
+        self.set_castling(self.castling & !castle_bit(team, side));
+    }
+
+    /// replaces all the castling rights at once, keeping the hash in step
+    fn set_castling(&mut self, castling: u8) {
+        for team in Team::all() {
+            for side in [CastleSide::KingSide, CastleSide::QueenSide] {
+                if (self.castling ^ castling) & castle_bit(team, side) != 0 {
+                    self.hash ^= ZOBRIST.castle(team, side);
+                }
+            }
+        }
+        self.castling = castling;
+    }
+
+    /// Gets the color-reversed position, flipped top to bottom with every piece swapping teams
+    ///
+    /// The side to move, castling rights and en passant square are reversed along with the pieces, so the
+    /// mirrored position plays out exactly like this one with the colors swapped. The clocks carry over,
+    /// but the earlier positions don't, so repetitions start counting afresh.
+    pub fn mirror(&self) -> MailboxBoard {
+        let mut mirrored = MailboxBoard::empty();
+
+        for coord in Coordinate::all() {
+            if let Some((team, chessman)) = self.get_tile(coord).data() {
+                mirrored.set_tile(coord.flip_vertical(), Piece::new(Some((team.opponent(), chessman))));
+            }
+        }
+
+        for team in Team::all() {
+            for side in [CastleSide::KingSide, CastleSide::QueenSide] {
+                if self.can_castle(team, side) {
+                    mirrored.grant_castle(team.opponent(), side);
+                }
+            }
+        }
+
+        mirrored.set_side_to_move(self.side_to_move.opponent());
+        mirrored.set_en_passant(self.en_passant.map(Coordinate::flip_vertical));
+        mirrored.set_halfmove_clock(self.halfmove_clock);
+        mirrored.set_fullmove_number(self.fullmove_number);
+        mirrored
+    }
+
+    /// Renders the board with chess glyphs, rank 8 at the top
+    #[cfg(feature = "std")]
+    pub fn to_unicode(&self) -> String {
+        let mut out = String::new();
+
+        for rank in (0..BOARD_LENGTH as u8).rev() {
+            for file in 0..BOARD_LENGTH as u8 {
+                let glyph = match self.tiles[(rank * BOARD_LENGTH as u8 + file) as usize].data() {
+                    None => '·',
+                    Some((Team::White, Chessman::King)) => '♔',
+                    Some((Team::White, Chessman::Queen)) => '♕',
+                    Some((Team::White, Chessman::Rook)) => '♖',
+                    Some((Team::White, Chessman::Bishop)) => '♗',
+                    Some((Team::White, Chessman::Knight)) => '♘',
+                    Some((Team::White, Chessman::Pawn)) => '♙',
+                    Some((Team::Black, Chessman::King)) => '♚',
+                    Some((Team::Black, Chessman::Queen)) => '♛',
+                    Some((Team::Black, Chessman::Rook)) => '♜',
+                    Some((Team::Black, Chessman::Bishop)) => '♝',
+                    Some((Team::Black, Chessman::Knight)) => '♞',
+                    Some((Team::Black, Chessman::Pawn)) => '♟',
+                };
+
+                if file > 0 {
+                    out.push(' ');
                 }
+                out.push(glyph);
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Renders the board in plain ASCII inside a border, with FEN letters for pieces and `.` for empty tiles
+    ///
+    /// Rank numbers run down the left side, rank 8 at the top, and the file letters go along the bottom.
+    #[cfg(feature = "std")]
+    pub fn to_ascii(&self) -> String {
+        let border = "  +-----------------+\n";
+        let mut out = String::from(border);
+
+        for rank in (0..BOARD_LENGTH as u8).rev() {
+            out.push_str(&format!("{} |", rank + 1));
+            for file in 0..BOARD_LENGTH as u8 {
+                let piece = self.tiles[(rank * BOARD_LENGTH as u8 + file) as usize];
+                out.push(' ');
+                out.push(piece.to_fen_char().unwrap_or('.'));
             }
+            out.push_str(" |\n");
         }
+
+        out.push_str(border);
+        out.push_str("    a b c d e f g h\n");
+        out
     }
 }
 
+impl MailboxBoard {
+    /// Parses a move in UCI long algebraic notation, like `e2e4` or `e7e8q`
+    ///
+    /// The moving and captured pieces are filled in from the board, this doesn't check that the move is legal.
+    pub fn parse_uci(&self, uci: &str) -> Result<Move, MoveParseError> {
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err(MoveParseError::InvalidLength);
+        }
+
+        let origin = Coordinate::from_algebraic(&uci[0..2]).map_err(MoveParseError::InvalidSquare)?;
+        let target = Coordinate::from_algebraic(&uci[2..4]).map_err(MoveParseError::InvalidSquare)?;
 
+        let piece = self.get_tile(origin);
+        let (team, chessman) = piece.data().ok_or(MoveParseError::EmptyOrigin)?;
+        if team != self.side_to_move {
+            return Err(MoveParseError::WrongTeam);
+        }
+
+        let capture = self.get_tile(target);
+        let is_capture = match capture.data() {
+            Some((occupant, _)) if occupant == team => return Err(MoveParseError::FriendlyTarget),
+            Some(_) => true,
+            None => false,
+        };
 
+        let last_rank = match team {
+            Team::White => BOARD_LENGTH as u8 - 1,
+            Team::Black => 0,
+        };
+        let promotes = chessman == Chessman::Pawn && target.rank() == last_rank;
 
+        let kind = match (uci[4..].chars().next(), promotes) {
+            (Some(_), false) => return Err(MoveParseError::UnexpectedPromotion),
+            (None, true) => return Err(MoveParseError::MissingPromotion),
+            (Some(letter), true) => match (letter, is_capture) {
+                ('n', false) => MoveKind::KnightPromotion,
+                ('b', false) => MoveKind::BishopPromotion,
+                ('r', false) => MoveKind::RookPromotion,
+                ('q', false) => MoveKind::QueenPromotion,
+                ('n', true) => MoveKind::KnightPromotionCapture,
+                ('b', true) => MoveKind::BishopPromotionCapture,
+                ('r', true) => MoveKind::RookPromotionCapture,
+                ('q', true) => MoveKind::QueenPromotionCapture,
+                (letter, _) => return Err(MoveParseError::InvalidPromotion(letter)),
+            },
+            (None, false) => {
+                let rank_distance = target.rank().abs_diff(origin.rank());
+                let file_distance = target.file().abs_diff(origin.file());
+
+                match chessman {
+                    Chessman::Pawn if rank_distance == 2 => MoveKind::DoublePawnPush,
+                    // a pawn moving diagonally onto an empty square can only be capturing en passant
+                    Chessman::Pawn if file_distance == 1 && !is_capture => MoveKind::EPCapture,
+                    Chessman::King if file_distance == 2 && target.file() > origin.file() => MoveKind::KingCastle,
+                    Chessman::King if file_distance == 2 => MoveKind::QueenCastle,
+                    _ if is_capture => MoveKind::Capture,
+                    _ => MoveKind::QuietMove,
+                }
+            }
+        };
+
+        // en passant takes the pawn that's beside the mover, not the one on the target square
+        let capture = if kind == MoveKind::EPCapture {
+            Piece::new(Some((team.opponent(), Chessman::Pawn)))
+        } else {
+            capture
+        };
+
+        Ok(Move::new(kind, origin, target, piece, capture))
+    }
+
+    /// Plays a space-separated list of UCI moves in order, like the `e2e4 e7e5` after `position startpos moves`
+    ///
+    /// Each move has to be legal in the position the ones before it leave. If one isn't, the error for it is
+    /// returned and the moves before it stay played.
+    pub fn apply_uci_moves(&mut self, moves: &str) -> Result<(), MoveParseError> {
+        for uci in moves.split_whitespace() {
+            let m = self.parse_uci(uci)?;
+            if !self.legal_moves().contains(&m) {
+                return Err(MoveParseError::IllegalMove);
+            }
+            self.make_move(m);
+        }
+
+        Ok(())
+    }
+}
+
+impl MailboxBoard {
+    /// gets the rook's origin and target square for a castling move
+    fn castle_rook(m: &Move) -> Option<(Coordinate, Coordinate)> {
+        let home = m.origin.rank() * BOARD_LENGTH as u8;
+
+        match m.kind {
+            MoveKind::KingCastle => Some((Coordinate { value: home + 7 }, Coordinate { value: home + 5 })),
+            MoveKind::QueenCastle => Some((Coordinate { value: home }, Coordinate { value: home + 3 })),
+            _ => None,
+        }
+    }
+
+    /// gets the square of the pawn taken by an en passant capture
+    pub(crate) fn en_passant_victim(m: &Move) -> Coordinate {
+        Coordinate { value: m.origin.rank() * BOARD_LENGTH as u8 + m.target.file() }
+    }
+
+    /// takes away any castling rights that depend on a piece still being on the square
+    fn revoke_castle_from(&mut self, square: Coordinate) {
+        let last = BOARD_LENGTH as u8 - 1;
+
+        match (square.rank(), square.file()) {
+            (0, 0) => self.revoke_castle(Team::White, CastleSide::QueenSide),
+            (0, f) if f == last => self.revoke_castle(Team::White, CastleSide::KingSide),
+            (r, 0) if r == last => self.revoke_castle(Team::Black, CastleSide::QueenSide),
+            (r, f) if r == last && f == last => self.revoke_castle(Team::Black, CastleSide::KingSide),
+            _ => {}
+        }
+    }
+
+    /// Makes a move on the board, returning what's needed to take it back with `unmake_move`
+    ///
+    /// The move isn't checked for legality.
+    pub fn make_move(&mut self, m: Move) -> Undo {
+        let undo = Undo {
+            m,
+            castling: self.castling,
+            en_passant: self.en_passant,
+            side_to_move: self.side_to_move,
+            repetition_start: self.repetition_start,
+            halfmove_clock: self.halfmove_clock,
+        };
+        self.history.push(self.hash);
+
+        let team = m.piece().data().map_or(self.side_to_move, |(team, _)| team);
+        let placed = match m.kind.promotion_piece() {
+            Some(chessman) => Piece::new(Some((team, chessman))),
+            None => m.piece(),
+        };
+
+        self.clear_tile(m.origin);
+        self.set_tile(m.target, placed);
+
+        if m.kind == MoveKind::EPCapture {
+            self.clear_tile(MailboxBoard::en_passant_victim(&m));
+        }
+
+        if let Some((rook_origin, rook_target)) = MailboxBoard::castle_rook(&m) {
+            let rook = self.get_tile(rook_origin);
+            self.clear_tile(rook_origin);
+            self.set_tile(rook_target, rook);
+        }
+
+        if m.piece().data() == Some((team, Chessman::King)) {
+            self.revoke_castle(team, CastleSide::KingSide);
+            self.revoke_castle(team, CastleSide::QueenSide);
+        }
+        // covers both a rook leaving its corner and a rook being captured there
+        self.revoke_castle_from(m.origin);
+        self.revoke_castle_from(m.target);
+
+        self.set_en_passant(match m.kind {
+            MoveKind::DoublePawnPush => Some(Coordinate { value: (m.origin.value + m.target.value) / 2 }),
+            _ => None,
+        });
+
+        self.set_side_to_move(self.side_to_move.opponent());
+
+        let irreversible = m.capture().data().is_some() || matches!(m.piece().data(), Some((_, Chessman::Pawn)));
+        if irreversible {
+            self.repetition_start = self.history.len();
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if undo.side_to_move == Team::Black {
+            self.fullmove_number += 1;
+        }
+
+        undo
+    }
+
+    /// Takes back a move made with `make_move`
+    ///
+    /// Undo tokens have to be given back in the reverse order the moves were made.
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let m = undo.m;
+
+        if let Some((rook_origin, rook_target)) = MailboxBoard::castle_rook(&m) {
+            let rook = self.get_tile(rook_target);
+            self.clear_tile(rook_target);
+            self.set_tile(rook_origin, rook);
+        }
+
+        self.set_tile(m.origin, m.piece());
+
+        if m.kind == MoveKind::EPCapture {
+            self.clear_tile(m.target);
+            self.set_tile(MailboxBoard::en_passant_victim(&m), m.capture());
+        } else {
+            self.set_tile(m.target, m.capture());
+        }
+
+        self.set_castling(undo.castling);
+        self.set_en_passant(undo.en_passant);
+        self.set_side_to_move(undo.side_to_move);
+
+        self.history.pop();
+        self.repetition_start = undo.repetition_start;
+        self.halfmove_clock = undo.halfmove_clock;
+        if undo.side_to_move == Team::Black {
+            self.fullmove_number -= 1;
+        }
+    }
+}
+
+impl Default for MailboxBoard {
+    fn default() -> Self {
+        MailboxBoard::starting_position()
+    }
+}
+
+impl PartialEq for MailboxBoard {
+    fn eq(&self, other: &Self) -> bool {
+        self.tiles == other.tiles
+            && self.side_to_move == other.side_to_move
+            && self.castling == other.castling
+            && self.en_passant == other.en_passant
+    }
+}
+
+impl Eq for MailboxBoard {}
+
+impl Hash for MailboxBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tiles.hash(state);
+        self.side_to_move.hash(state);
+        self.castling.hash(state);
+        self.en_passant.hash(state);
+    }
+}
+
+impl Board for MailboxBoard {
+    fn set_tile(&mut self, coord: Coordinate, piece: Piece) {
+        let tile = &mut self.tiles[coord.value as usize];
+        self.hash ^= ZOBRIST.piece(*tile, coord) ^ ZOBRIST.piece(piece, coord);
+        *tile = piece;
+    }
+
+    fn clear_tile(&mut self, coord: Coordinate) {
+        self.set_tile(coord, Piece::new(None));
+    }
+
+    fn get_moves(&self) -> Vec<Move> {
+        movegen::team_moves(self, self.side_to_move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece() {
+        for team in Team::all() {
+            for chessman in Chessman::all() {
+                let piece = Piece::new(Some((team, chessman)));
+
+                assert_eq!(piece.data(), Some((team, chessman)));
+            }
+        }
+
+        let piece = Piece::new(None);
+        assert_eq!(piece.data(), None);
+    }
+
+    #[test]
+    fn test_all() {
+        let teams = Team::all();
+        for (i, team) in teams.iter().enumerate() {
+            assert_eq!(*team as usize, i);
+        }
+        assert_eq!(teams, [Team::White, Team::Black]);
+
+        let chessmen = Chessman::all();
+        for (i, chessman) in chessmen.iter().enumerate() {
+            assert_eq!(*chessman as usize, i);
+        }
+        for chessman in chessmen {
+            assert_eq!(chessmen.iter().filter(|&&other| other == chessman).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_move_size() {
+        assert_eq!(core::mem::size_of::<Move>(), 4);
+        assert_eq!(core::mem::size_of::<Option<Move>>(), 4);
+
+        let mut pieces = vec![Piece::new(None)];
+        for team in Team::all() {
+            pieces.extend(Chessman::all().map(|chessman| Piece::new(Some((team, chessman)))));
+        }
+
+        let origin = Coordinate::from_algebraic("b7").unwrap();
+        let target = Coordinate::from_algebraic("a8").unwrap();
+        for &piece in &pieces {
+            for &capture in &pieces {
+                let m = Move::new(MoveKind::QueenPromotionCapture, origin, target, piece, capture);
+                assert_eq!((m.piece(), m.capture()), (piece, capture));
+                assert_eq!((m.kind(), m.origin(), m.target()), (MoveKind::QueenPromotionCapture, origin, target));
+            }
+        }
+    }
+
+    #[test]
+    fn test_move_kind() {
+        assert_eq!(MoveKind::QueenPromotionCapture.promotion_piece(), Some(Chessman::Queen));
+        assert!(MoveKind::QueenPromotionCapture.is_capture());
+        assert!(MoveKind::QueenPromotionCapture.is_promotion());
+
+        assert_eq!(MoveKind::KnightPromotion.promotion_piece(), Some(Chessman::Knight));
+        assert!(!MoveKind::KnightPromotion.is_capture());
+        assert!(MoveKind::EPCapture.is_capture());
+        assert!(!MoveKind::EPCapture.is_promotion());
+        assert_eq!(MoveKind::Capture.promotion_piece(), None);
+        assert!(!MoveKind::KingCastle.is_capture());
+        assert!(!MoveKind::DoublePawnPush.is_promotion());
+    }
+
+    #[test]
+    fn test_move() {
+        let origin = Coordinate::from_algebraic("e2").unwrap();
+        let target = Coordinate::from_algebraic("e3").unwrap();
+        let pawn = Piece::new(Some((Team::White, Chessman::Pawn)));
+
+        let m = Move::new(MoveKind::QuietMove, origin, target, pawn, Piece::new(None));
+        assert_eq!(m.kind(), MoveKind::QuietMove);
+        assert_eq!(m.origin(), origin);
+        assert_eq!(m.target(), target);
+        assert_eq!(m.piece(), pawn);
+        assert_eq!(m.capture(), Piece::new(None));
+
+        let copy = m;
+        assert_eq!(copy, m);
+        assert_ne!(Move::new(MoveKind::DoublePawnPush, origin, target, pawn, Piece::new(None)), m);
+    }
+
+    #[test]
+    fn test_move_to_uci() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+        let pawn = Piece::new(Some((Team::White, Chessman::Pawn)));
+        let knight = Piece::new(Some((Team::Black, Chessman::Knight)));
+
+        let quiet = Move::new(MoveKind::DoublePawnPush, square("e2"), square("e4"), pawn, Piece::new(None));
+        assert_eq!(quiet.to_uci(), "e2e4");
+
+        let capture = Move::new(MoveKind::Capture, square("e4"), square("d5"), pawn, knight);
+        assert_eq!(capture.to_uci(), "e4d5");
+
+        for (kind, uci) in [
+            (MoveKind::QueenPromotion, "e7e8q"),
+            (MoveKind::RookPromotion, "e7e8r"),
+            (MoveKind::BishopPromotion, "e7e8b"),
+            (MoveKind::KnightPromotion, "e7e8n"),
+        ] {
+            assert_eq!(Move::new(kind, square("e7"), square("e8"), pawn, Piece::new(None)).to_uci(), uci);
+        }
+
+        for (kind, uci) in [
+            (MoveKind::QueenPromotionCapture, "e7f8q"),
+            (MoveKind::RookPromotionCapture, "e7f8r"),
+            (MoveKind::BishopPromotionCapture, "e7f8b"),
+            (MoveKind::KnightPromotionCapture, "e7f8n"),
+        ] {
+            assert_eq!(Move::new(kind, square("e7"), square("f8"), pawn, knight).to_uci(), uci);
+        }
+    }
+
+    #[test]
+    fn test_piece_equality() {
+        assert_eq!(Piece::default(), Piece::new(None));
+        assert_eq!(Piece::default().data(), None);
+
+        let king = Piece::new(Some((Team::White, Chessman::King)));
+        assert_eq!(king, Piece::new(Some((Team::White, Chessman::King))));
+        assert_ne!(king, Piece::default());
+        assert_ne!(king, Piece::new(Some((Team::Black, Chessman::King))));
+    }
+
+    #[test]
+    fn test_error_display() {
+        for error in [Error::OutOfBoundsAxis, Error::OutOfBoundsIndex, Error::InvalidAlgebraic, Error::InvalidPieceCharacter('x')] {
+            assert!(!error.to_string().is_empty());
+        }
+
+        let boxed: Box<dyn std::error::Error> = Box::new(Error::OutOfBoundsAxis);
+        assert_eq!(boxed.to_string(), "coordinate axis out of bounds");
+    }
+
+    #[test]
+    fn test_team_opponent() {
+        assert_eq!(Team::White.opponent(), Team::Black);
+        assert_eq!(Team::Black.opponent(), Team::White);
+
+        for team in Team::all() {
+            assert_eq!(team.opponent().opponent(), team);
+        }
+    }
+
+    #[test]
+    fn test_team_and_chessman_display() {
+        assert_eq!(Team::White.to_string(), "White");
+        assert_eq!(Team::Black.to_string(), "Black");
+        assert_eq!(Chessman::King.to_string(), "King");
+        assert_eq!(Chessman::Knight.to_string(), "Knight");
+        assert_eq!(Chessman::Pawn.to_string(), "Pawn");
+    }
+
+    #[test]
+    fn test_chessman_chars() {
+        let letters = [
+            (Chessman::King, 'k'),
+            (Chessman::Queen, 'q'),
+            (Chessman::Bishop, 'b'),
+            (Chessman::Knight, 'n'),
+            (Chessman::Rook, 'r'),
+            (Chessman::Pawn, 'p'),
+        ];
+
+        for (chessman, letter) in letters {
+            assert_eq!(chessman.to_char(), letter);
+            assert_eq!(Chessman::from_char(letter), Some(chessman));
+            assert_eq!(Chessman::from_char(letter.to_ascii_uppercase()), Some(chessman));
+        }
+
+        for bad in ['x', '1', ' ', 'é'] {
+            assert_eq!(Chessman::from_char(bad), None);
+        }
+    }
+
+    #[test]
+    fn test_piece_fen_chars() {
+        for letter in "KQBNRPkqbnrp".chars() {
+            let piece = Piece::try_from(letter).expect("valid piece letter");
+            assert_eq!(piece.to_fen_char(), Some(letter));
+        }
+
+        assert_eq!(Piece::try_from('K'), Ok(Piece::new(Some((Team::White, Chessman::King)))));
+        assert_eq!(Piece::try_from('p'), Ok(Piece::new(Some((Team::Black, Chessman::Pawn)))));
+        assert_eq!(Piece::try_from('x'), Err(Error::InvalidPieceCharacter('x')));
+        assert_eq!(Piece::try_from('3'), Err(Error::InvalidPieceCharacter('3')));
+        assert_eq!(Piece::new(None).to_fen_char(), None);
+    }
+
+    #[test]
+    fn test_chessman_value() {
+        assert!(Chessman::Queen.value() > Chessman::Rook.value());
+        assert!(Chessman::Rook.value() > Chessman::Bishop.value());
+        assert!((Chessman::Bishop.value() - Chessman::Knight.value()).abs() < Chessman::Pawn.value());
+        assert!(Chessman::Knight.value() > Chessman::Pawn.value());
+        assert_eq!(Chessman::King.value(), 0);
+    }
+
+    #[test]
+    fn test_coordinate_offset() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+
+        assert_eq!(square("h1").offset(0, 1), None);
+        assert_eq!(square("a8").offset(1, 0), None);
+        assert_eq!(square("a4").offset(0, -1), None);
+        assert_eq!(square("e4").offset(1, 1), Some(square("f5")));
+        assert_eq!(square("b1").offset(2, -1), Some(square("a3")));
+        assert_eq!(square("e4").offset(0, 0), Some(square("e4")));
+    }
+
+    #[test]
+    fn test_coordinate_flip_vertical() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+
+        assert_eq!(square("a1").flip_vertical(), square("a8"));
+        assert_eq!(square("e4").flip_vertical(), square("e5"));
+        assert_eq!(square("h7").flip_vertical(), square("h2"));
+        assert!(Coordinate::all().all(|coord| coord.flip_vertical().flip_vertical() == coord));
+    }
+
+    #[test]
+    fn test_coordinate_is_light() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+
+        assert!(!square("a1").is_light());
+        assert!(square("h1").is_light());
+        assert!(square("a8").is_light());
+        assert!(!square("d4").is_light());
+        assert!(square("e4").is_light());
+        assert_eq!(Coordinate::all().filter(|coord| coord.is_light()).count(), 32);
+    }
+
+    #[test]
+    fn test_board_eq_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |board: &MailboxBoard| {
+            let mut hasher = DefaultHasher::new();
+            board.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let start = MailboxBoard::starting_position();
+        let parsed = MailboxBoard::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert!(start == parsed);
+        assert_eq!(hash(&start), hash(&parsed));
+
+        // the moves played to get here don't count
+        let mut played = MailboxBoard::starting_position();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            played.make_move(played.parse_uci(uci).unwrap());
+        }
+        assert!(played == start);
+        assert_eq!(hash(&played), hash(&start));
+
+        let mut en_passant = parsed.clone();
+        en_passant.set_en_passant(Some(Coordinate::from_algebraic("e3").unwrap()));
+        assert!(en_passant != parsed);
+        let mut castling = parsed.clone();
+        castling.revoke_castle(Team::Black, CastleSide::QueenSide);
+        assert!(castling != parsed);
+
+        let boards: std::collections::HashSet<MailboxBoard> = [start, parsed, played, en_passant].into_iter().collect();
+        assert_eq!(boards.len(), 2);
+    }
+
+    #[test]
+    fn test_mirror() {
+        // reversing the colors of the start just hands Black the first move
+        let mirrored = MailboxBoard::starting_position().mirror();
+        assert_eq!(mirrored.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+
+        let board = MailboxBoard::from_fen("r3k2r/8/8/3pP3/8/8/8/4K2R w Kq d6 5 1").unwrap();
+        let mirrored = board.mirror();
+        assert_eq!(mirrored.to_fen(), "4k2r/8/8/8/3Pp3/8/8/R3K2R b Qk d3 5 1");
+        assert_eq!(mirrored.mirror().to_fen(), board.to_fen());
+        assert_eq!(mirrored.mirror().zobrist_hash(), board.zobrist_hash());
+    }
+
+    #[test]
+    fn test_coordinate() {
+        for file in 0..=BOARD_LENGTH {
+            for rank in 0..=BOARD_LENGTH {
+                let file = file as u8;
+                let rank = rank as u8;
+
+                let test_coord = Coordinate::try_from((rank, file));
+
+                if file < BOARD_LENGTH as u8 && rank < BOARD_LENGTH as u8 {
+                    let test_coord = test_coord.expect("try_from failed");
+                    assert_eq!(test_coord.rank(), rank);
+                    assert_eq!(test_coord.file(), file);
+                } else {
+                    assert_eq!(test_coord, Err(Error::OutOfBoundsAxis));
+                }
+
+                let index = (rank * BOARD_LENGTH as u8) | file;
+                let test_coord = Coordinate::try_from(index);
+
+                if index < NUM_TILES as u8 {
+                    let test_coord = test_coord.expect("try_from failed");
+                    // just comparing to rank/file doesnt work because adding too high file to not too big rank might not be out of range
+                    assert_eq!(test_coord.rank(), index / BOARD_LENGTH as u8);
+                    assert_eq!(test_coord.file(), index % BOARD_LENGTH as u8);
+                } else {
+                    assert_eq!(test_coord, Err(Error::OutOfBoundsIndex))
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_coordinate_index() {
+        for index in 0..NUM_TILES as u8 {
+            let coord = Coordinate::new_unchecked(index);
+            assert_eq!(coord.index(), index);
+            assert_eq!(Coordinate::try_from(index), Ok(coord));
+            assert_eq!(Coordinate::try_from((coord.rank(), coord.file())), Ok(coord));
+        }
+    }
+
+    #[test]
+    fn test_from_algebraic() {
+        assert_eq!(Coordinate::from_algebraic("a1"), Ok(Coordinate { value: 0 }));
+        assert_eq!(Coordinate::from_algebraic("h8"), Ok(Coordinate { value: 63 }));
+        assert_eq!("e4".parse::<Coordinate>(), Coordinate::try_from((3, 4)));
+
+        for bad in ["i1", "a9", "a0", "e", "", "e44", "E4", "4e"] {
+            assert_eq!(Coordinate::from_algebraic(bad), Err(Error::InvalidAlgebraic), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_coordinate_display() {
+        assert_eq!(Coordinate::try_from((3, 4)).unwrap().to_string(), "e4");
+
+        for index in 0..NUM_TILES as u8 {
+            let coord = Coordinate::try_from(index).expect("try_from failed");
+            assert_eq!(Coordinate::from_algebraic(&coord.to_string()), Ok(coord));
+        }
+    }
+
+    #[test]
+    fn test_coordinate_all() {
+        let all: Vec<Coordinate> = Coordinate::all().collect();
+        assert_eq!(all.len(), NUM_TILES);
+
+        let mut seen = std::collections::HashSet::new();
+        for (index, coord) in all.iter().enumerate() {
+            assert_eq!(coord.index() as usize, index);
+            assert!(seen.insert((coord.rank(), coord.file())));
+        }
+        assert_eq!(seen.len(), NUM_TILES);
+    }
+
+    #[test]
+    fn test_to_unicode() {
+        let rendered = MailboxBoard::starting_position().to_unicode();
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(lines.len(), BOARD_LENGTH);
+        assert_eq!(lines[0], "♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜");
+        assert_eq!(lines[1], "♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟");
+        assert_eq!(lines[4], "· · · · · · · ·");
+        assert_eq!(lines[7], "♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖");
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        let rendered = MailboxBoard::starting_position().to_ascii();
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(lines.len(), BOARD_LENGTH + 3);
+        assert_eq!(lines[0], "  +-----------------+");
+        assert_eq!(lines[1], "8 | r n b q k b n r |");
+        assert_eq!(lines[5], "4 | . . . . . . . . |");
+        assert_eq!(lines[8], "1 | R N B Q K B N R |");
+        assert_eq!(lines[9], lines[0]);
+        assert_eq!(lines[10].trim(), "a b c d e f g h");
+    }
+
+    #[test]
+    fn test_apply_uci_moves() {
+        let mut board = MailboxBoard::starting_position();
+        assert_eq!(board.apply_uci_moves("e2e4 e7e5 g1f3"), Ok(()));
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+
+        // castling and en passant are worked out along the way
+        assert_eq!(board.apply_uci_moves("  b8c6 f1e2 d7d5 e1g1 d5d4 c2c4 d4c3 "), Ok(()));
+        assert_eq!(board.to_fen(), "r1bqkbnr/ppp2ppp/2n5/4p3/4P3/2p2N2/PP1PBPPP/RNBQ1RK1 w kq - 0 6");
+
+        let mut board = MailboxBoard::starting_position();
+        assert_eq!(board.apply_uci_moves("e2e4 e7e5 e1e3 g1f3"), Err(MoveParseError::IllegalMove));
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+        assert_eq!(board.apply_uci_moves("e4"), Err(MoveParseError::InvalidLength));
+        assert_eq!(board.apply_uci_moves(""), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_uci() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+        let board = MailboxBoard::starting_position();
+
+        let m = board.parse_uci("e2e4").expect("parse_uci failed");
+        assert_eq!(m.kind(), MoveKind::DoublePawnPush);
+        assert_eq!(m.origin(), square("e2"));
+        assert_eq!(m.target(), square("e4"));
+        assert_eq!(m.piece(), Piece::new(Some((Team::White, Chessman::Pawn))));
+        assert_eq!(m.capture(), Piece::new(None));
+        assert_eq!(m.to_uci(), "e2e4");
+
+        assert_eq!(board.parse_uci("g1f3").map(|m| m.kind()), Ok(MoveKind::QuietMove));
+
+        assert_eq!(board.parse_uci("e4e5"), Err(MoveParseError::EmptyOrigin));
+        assert_eq!(board.parse_uci("e7e5"), Err(MoveParseError::WrongTeam));
+        assert_eq!(board.parse_uci("d1d2"), Err(MoveParseError::FriendlyTarget));
+        assert_eq!(board.parse_uci("e2e"), Err(MoveParseError::InvalidLength));
+        assert_eq!(board.parse_uci("e2e4e4"), Err(MoveParseError::InvalidLength));
+        assert_eq!(board.parse_uci("e2e9"), Err(MoveParseError::InvalidSquare(Error::InvalidAlgebraic)));
+        assert_eq!(board.parse_uci("e2e4q"), Err(MoveParseError::UnexpectedPromotion));
+    }
+
+    #[test]
+    fn test_parse_uci_special_moves() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("e1"), Piece::new(Some((Team::White, Chessman::King))));
+        board.set_tile(square("h1"), Piece::new(Some((Team::White, Chessman::Rook))));
+        board.set_tile(square("b7"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("e5"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("a8"), Piece::new(Some((Team::Black, Chessman::Rook))));
+        board.set_tile(square("d5"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+
+        assert_eq!(board.parse_uci("e1g1").map(|m| m.kind()), Ok(MoveKind::KingCastle));
+        assert_eq!(board.parse_uci("e1c1").map(|m| m.kind()), Ok(MoveKind::QueenCastle));
+        assert_eq!(board.parse_uci("b7b8n").map(|m| m.kind()), Ok(MoveKind::KnightPromotion));
+        assert_eq!(board.parse_uci("b7a8q").map(|m| m.kind()), Ok(MoveKind::QueenPromotionCapture));
+        assert_eq!(board.parse_uci("b7b8"), Err(MoveParseError::MissingPromotion));
+        assert_eq!(board.parse_uci("b7b8k"), Err(MoveParseError::InvalidPromotion('k')));
+
+        let ep = board.parse_uci("e5d6").expect("parse_uci failed");
+        assert_eq!(ep.kind(), MoveKind::EPCapture);
+        assert_eq!(ep.capture(), Piece::new(Some((Team::Black, Chessman::Pawn))));
+    }
+
+    #[test]
+    fn test_castling_rights() {
+        let mut board = MailboxBoard::starting_position();
+        for team in Team::all() {
+            for side in [CastleSide::KingSide, CastleSide::QueenSide] {
+                assert!(board.can_castle(team, side));
+            }
+        }
+
+        board.revoke_castle(Team::White, CastleSide::QueenSide);
+        assert!(!board.can_castle(Team::White, CastleSide::QueenSide));
+        assert!(board.can_castle(Team::White, CastleSide::KingSide));
+        assert!(board.can_castle(Team::Black, CastleSide::QueenSide));
+
+        // revoking twice is harmless
+        board.revoke_castle(Team::White, CastleSide::QueenSide);
+        assert!(!board.can_castle(Team::White, CastleSide::QueenSide));
+
+        board.grant_castle(Team::White, CastleSide::QueenSide);
+        assert!(board.can_castle(Team::White, CastleSide::QueenSide));
+
+        let board = MailboxBoard::empty();
+        assert!(!board.can_castle(Team::Black, CastleSide::KingSide));
+    }
+
+    #[test]
+    fn test_make_unmake_random_sequence() {
+        // small LCG so the game is random looking but repeatable
+        let mut seed: u32 = 0x1234_5678;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as usize
+        };
+
+        let mut board = MailboxBoard::starting_position();
+        let start = board.to_fen();
+        let mut undos = Vec::new();
+        let mut fens = Vec::new();
+
+        for _ in 0..60 {
+            let moves = board.get_moves();
+            if moves.is_empty() {
+                break;
+            }
+
+            fens.push(board.to_fen());
+            undos.push(board.make_move(moves[next() % moves.len()]));
+        }
+
+        while let Some(undo) = undos.pop() {
+            board.unmake_move(undo);
+            assert_eq!(board.to_fen(), fens.pop().unwrap());
+        }
+
+        assert_eq!(board.to_fen(), start);
+        let expected = MailboxBoard::starting_position();
+        for index in 0..NUM_TILES as u8 {
+            let coord = Coordinate::new_unchecked(index);
+            assert_eq!(board.get_tile(coord), expected.get_tile(coord));
+        }
+    }
+
+    #[test]
+    fn test_make_move_special_moves() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+
+        // castling moves the rook too and loses both rights
+        let mut board = MailboxBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let undo = board.make_move(board.parse_uci("e1g1").unwrap());
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+        board.unmake_move(undo);
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+
+        let undo = board.make_move(board.parse_uci("e1c1").unwrap());
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/2KR3R b kq - 1 1");
+        board.unmake_move(undo);
+
+        // moving a rook loses its side, capturing a rook loses the other team's side
+        board.make_move(board.parse_uci("a1a8").unwrap());
+        assert_eq!(board.to_fen(), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1");
+
+        // double pushes set the en passant square, which then gets taken
+        let mut board = MailboxBoard::from_fen("4k3/3p4/8/4P3/8/8/8/4K3 b - - 0 1").unwrap();
+        board.make_move(board.parse_uci("d7d5").unwrap());
+        assert_eq!(board.en_passant(), Some(square("d6")));
+
+        let start = board.to_fen();
+        let undo = board.make_move(board.parse_uci("e5d6").unwrap());
+        assert_eq!(board.to_fen(), "4k3/8/3P4/8/8/8/8/4K3 b - - 0 2");
+        board.unmake_move(undo);
+        assert_eq!(board.to_fen(), start);
+
+        // promotions replace the pawn
+        let mut board = MailboxBoard::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let undo = board.make_move(board.parse_uci("a7b8n").unwrap());
+        assert_eq!(board.to_fen(), "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        board.unmake_move(undo);
+        assert_eq!(board.to_fen(), "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn test_threefold_repetition() {
+        let mut board = MailboxBoard::starting_position();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let mut undos = Vec::new();
+
+        // the starting position comes up a second time, then a third
+        for repeat in 0..2 {
+            for (i, uci) in shuffle.iter().enumerate() {
+                assert!(!board.is_threefold_repetition(), "repeat {} move {}", repeat, i);
+                assert_eq!(board.is_repetition(), repeat > 0, "repeat {} move {}", repeat, i);
+                undos.push(board.make_move(board.parse_uci(uci).unwrap()));
+            }
+        }
+        assert!(board.is_threefold_repetition());
+
+        board.unmake_move(undos.pop().unwrap());
+        assert!(!board.is_threefold_repetition());
+        undos.push(board.make_move(board.parse_uci("f6g8").unwrap()));
+        assert!(board.is_threefold_repetition());
+
+        // a pawn move means the earlier positions can't come up again
+        let mut board = MailboxBoard::starting_position();
+        for uci in shuffle {
+            board.make_move(board.parse_uci(uci).unwrap());
+        }
+        board.make_move(board.parse_uci("e2e4").unwrap());
+        board.make_move(board.parse_uci("g8f6").unwrap());
+
+        // right after e4 there's an en passant square, so 1... Nf6 is the first position that can repeat
+        for uci in ["g1f3", "f6g8", "f3g1", "g8f6", "g1f3", "f6g8", "f3g1"] {
+            assert!(!board.is_threefold_repetition());
+            board.make_move(board.parse_uci(uci).unwrap());
+        }
+        assert!(!board.is_threefold_repetition());
+        board.make_move(board.parse_uci("g8f6").unwrap());
+        assert!(board.is_threefold_repetition());
+    }
+
+    #[test]
+    fn test_fifty_move_rule() {
+        let mut board = MailboxBoard::starting_position();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        for i in 0..100 {
+            assert_eq!(board.halfmove_clock(), i);
+            assert!(!board.is_fifty_move_draw());
+            board.make_move(board.parse_uci(shuffle[i as usize % shuffle.len()]).unwrap());
+        }
+        assert_eq!(board.halfmove_clock(), 100);
+        assert!(board.is_fifty_move_draw());
+
+        // a pawn move partway through starts the count over
+        let mut board = MailboxBoard::starting_position();
+        for i in 0..60 {
+            board.make_move(board.parse_uci(shuffle[i % shuffle.len()]).unwrap());
+        }
+        assert_eq!(board.halfmove_clock(), 60);
+
+        let undo = board.make_move(board.parse_uci("e2e4").unwrap());
+        assert_eq!(board.halfmove_clock(), 0);
+        board.unmake_move(undo);
+        assert_eq!(board.halfmove_clock(), 60);
+        board.make_move(board.parse_uci("e2e4").unwrap());
+
+        let black_first = ["g8f6", "g1f3", "f6g8", "f3g1"];
+        for i in 0..99 {
+            board.make_move(board.parse_uci(black_first[i % black_first.len()]).unwrap());
+        }
+        assert_eq!(board.halfmove_clock(), 99);
+        assert!(!board.is_fifty_move_draw());
+        board.make_move(board.parse_uci(black_first[99 % black_first.len()]).unwrap());
+        assert!(board.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_set_tiles() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+        let placements = [
+            (square("e1"), Piece::new(Some((Team::White, Chessman::King)))),
+            (square("d1"), Piece::new(Some((Team::White, Chessman::Rook)))),
+            (square("a7"), Piece::new(Some((Team::White, Chessman::Pawn)))),
+            (square("e8"), Piece::new(Some((Team::Black, Chessman::King)))),
+            (square("h5"), Piece::new(Some((Team::Black, Chessman::Bishop)))),
+        ];
+
+        let mut board = MailboxBoard::empty();
+        board.set_tiles(&placements);
+        for (coord, piece) in placements {
+            assert_eq!(board.get_tile(coord), piece, "{}", coord);
+        }
+        assert_eq!(Coordinate::all().filter(|&coord| board.get_tile(coord).data().is_some()).count(), 5);
+        assert_eq!(board.to_fen(), "4k3/P7/8/7b/8/8/8/3RK3 w - - 0 1");
+
+        // the hash is kept up to date the same way as with set_tile
+        assert_eq!(board.zobrist_hash(), MailboxBoard::from_fen(&board.to_fen()).unwrap().zobrist_hash());
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut board = MailboxBoard::starting_position();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            board.make_move(board.parse_uci(uci).unwrap());
+        }
+        let fen = board.to_fen();
+        let hash = board.zobrist_hash();
+
+        // the copy brings the history with it, so it knows about the repetition too
+        let mut branch = board.clone();
+        assert!(branch == board);
+        assert!(branch.is_repetition());
+        assert_eq!(branch.to_fen(), fen);
+
+        branch.make_move(branch.parse_uci("e2e4").unwrap());
+        assert!(!branch.is_repetition());
+        assert_eq!(branch.fullmove_number(), 3);
+
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.zobrist_hash(), hash);
+        assert!(board.is_repetition());
+        assert_eq!(board.legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn test_fullmove_number() {
+        let mut board = MailboxBoard::starting_position();
+        assert_eq!(board.fullmove_number(), 1);
+
+        let mut undos = Vec::new();
+        for (uci, number) in [("e2e4", 1), ("e7e5", 2), ("g1f3", 2)] {
+            undos.push(board.make_move(board.parse_uci(uci).unwrap()));
+            assert_eq!(board.fullmove_number(), number);
+        }
+
+        for number in [2, 1, 1] {
+            board.unmake_move(undos.pop().unwrap());
+            assert_eq!(board.fullmove_number(), number);
+        }
+    }
+
+    #[test]
+    fn test_count() {
+        let board = MailboxBoard::starting_position();
+
+        for team in Team::all() {
+            let expected = [
+                (Chessman::Pawn, 8),
+                (Chessman::Rook, 2),
+                (Chessman::Knight, 2),
+                (Chessman::Bishop, 2),
+                (Chessman::Queen, 1),
+                (Chessman::King, 1),
+            ];
+            let counts = board.material_count(team);
+            for (chessman, count) in expected {
+                assert_eq!(board.count(team, chessman), count);
+                assert_eq!(counts[chessman as usize], count);
+            }
+        }
+
+        let board = MailboxBoard::from_fen("4k3/8/8/8/8/8/1PP5/QQ2K3 w - - 0 1").unwrap();
+        assert_eq!(board.material_count(Team::White), [1, 2, 0, 0, 0, 2]);
+        assert_eq!(board.material_count(Team::Black), [1, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let validate = |fen: &str| MailboxBoard::from_fen(fen).unwrap().validate();
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+
+        assert_eq!(MailboxBoard::starting_position().validate(), Ok(()));
+        assert_eq!(validate("4k3/8/8/8/8/8/8/4K2R b K - 0 1"), Ok(()));
+        // the side to move being in check is fine
+        assert_eq!(validate("4k3/8/8/8/8/8/8/4K2r w - - 0 1"), Ok(()));
+
+        assert_eq!(validate("8/8/8/8/8/8/8/4K3 w - - 0 1"), Err(PositionError::MissingKing(Team::Black)));
+        assert_eq!(validate("4k3/8/8/8/8/8/8/8 b - - 0 1"), Err(PositionError::MissingKing(Team::White)));
+        assert_eq!(validate("4k3/8/8/8/8/8/8/K3K3 w - - 0 1"), Err(PositionError::TooManyKings(Team::White)));
+        assert_eq!(validate("k3k3/8/8/8/8/8/8/4K3 w - - 0 1"), Err(PositionError::TooManyKings(Team::Black)));
+        assert_eq!(validate("4k3/pppppppp/p7/8/8/8/8/4K3 w - - 0 1"), Err(PositionError::TooManyPawns(Team::Black)));
+        assert_eq!(validate("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"), Err(PositionError::PawnOnBackRank(square("a1"))));
+        assert_eq!(validate("3pk3/8/8/8/8/8/8/4K3 w - - 0 1"), Err(PositionError::PawnOnBackRank(square("d8"))));
+        assert_eq!(validate("4k3/8/8/8/8/8/8/4K2r b - - 0 1"), Err(PositionError::OpponentInCheck));
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        let dead = [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",
+            "4kn2/8/8/8/8/8/8/4K3 w - - 0 1",
+            // c1 and f8 are both dark squares
+            "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        ];
+        for fen in dead {
+            assert!(MailboxBoard::from_fen(fen).unwrap().is_insufficient_material(), "{}", fen);
+        }
+
+        let alive = [
+            "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/3QK3 w - - 0 1",
+            // d8 is a dark square but f1 is light
+            "3bk3/8/8/8/8/8/8/4KB2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+            "4kn2/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        ];
+        for fen in alive {
+            assert!(!MailboxBoard::from_fen(fen).unwrap().is_insufficient_material(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_mailbox_board() {
+        let mut board = MailboxBoard::empty();
+
+        for index in 0..NUM_TILES as u8 {
+            let coord = Coordinate::try_from(index).expect("try_from failed");
+            assert_eq!(board.get_tile(coord).data(), None);
+        }
+
+        let placements = [
+            (0, Team::White, Chessman::Rook),
+            (4, Team::White, Chessman::King),
+            (35, Team::Black, Chessman::Pawn),
+            (63, Team::Black, Chessman::Queen),
+        ];
+
+        for (index, team, chessman) in placements {
+            let coord = Coordinate::try_from(index).expect("try_from failed");
+            board.set_tile(coord, Piece::new(Some((team, chessman))));
+        }
+
+        for (index, team, chessman) in placements {
+            let coord = Coordinate::try_from(index).expect("try_from failed");
+            assert_eq!(board.get_tile(coord).data(), Some((team, chessman)));
+        }
+
+        // overwriting a tile replaces whatever was there
+        let coord = Coordinate::try_from(35u8).expect("try_from failed");
+        board.set_tile(coord, Piece::new(Some((Team::White, Chessman::Knight))));
+        assert_eq!(board.get_tile(coord).data(), Some((Team::White, Chessman::Knight)));
+
+        board.clear_tile(coord);
+        assert_eq!(board.get_tile(coord).data(), None);
+    }
+
+    #[test]
+    fn test_starting_position() {
+        let board = MailboxBoard::default();
+
+        let tile = |rank: u8, file: u8| board.get_tile(Coordinate::try_from((rank, file)).expect("try_from failed")).data();
+
+        assert_eq!(tile(0, 0), Some((Team::White, Chessman::Rook)));
+        assert_eq!(tile(0, 4), Some((Team::White, Chessman::King)));
+        assert_eq!(tile(7, 4), Some((Team::Black, Chessman::King)));
+
+        // rank 8 comes first in FEN
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+        for (row, rank_str) in fen.split('/').enumerate() {
+            let rank = (BOARD_LENGTH - 1 - row) as u8;
+
+            if rank_str == "8" {
+                for file in 0..BOARD_LENGTH as u8 {
+                    assert_eq!(tile(rank, file), None);
+                }
+                continue;
+            }
+
+            for (file, c) in rank_str.chars().enumerate() {
+                let team = if c.is_ascii_uppercase() { Team::White } else { Team::Black };
+                let chessman = match c.to_ascii_lowercase() {
+                    'k' => Chessman::King,
+                    'q' => Chessman::Queen,
+                    'b' => Chessman::Bishop,
+                    'n' => Chessman::Knight,
+                    'r' => Chessman::Rook,
+                    'p' => Chessman::Pawn,
+                    _ => panic!("bad test FEN"),
+                };
+
+                assert_eq!(tile(rank, file as u8), Some((team, chessman)));
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::board::{MailboxBoard, Move};
+use crate::search;
+
+/// takes the first whitespace-separated field off the front of some text
+fn next_field(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    (&text[..end], &text[end..])
+}
+
+/// Searches the position in an EPD record and checks the engine's move against the record's opcodes
+///
+/// A record is the first four FEN fields followed by `;`-terminated operations, like
+/// `6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id "back rank";`. The move found by `search_iterative` at the
+/// given depth passes if it's one of the `bm` best moves and none of the `am` moves to avoid, both given in SAN.
+/// Operations other than those are ignored. A record that can't be read, or that has neither, doesn't pass.
+pub fn run_epd(line: &str, depth: u8) -> bool {
+    let mut fen = Vec::new();
+    let mut rest = line;
+    for _ in 0..4 {
+        let (field, remaining) = next_field(rest);
+        fen.push(field);
+        rest = remaining;
+    }
+    let Ok(mut board) = MailboxBoard::from_fen(&fen.join(" ")) else { return false };
+
+    let mut best_moves: Option<Vec<Move>> = None;
+    let mut avoid_moves: Option<Vec<Move>> = None;
+    for operation in rest.split(';') {
+        let (opcode, operands) = next_field(operation);
+        let list = match opcode {
+            "bm" => &mut best_moves,
+            "am" => &mut avoid_moves,
+            _ => continue,
+        };
+
+        let Ok(moves) = operands.split_whitespace().map(|san| board.parse_san(san)).collect() else { return false };
+        *list = Some(moves);
+    }
+    if best_moves.is_none() && avoid_moves.is_none() {
+        return false;
+    }
+
+    let Some((m, _)) = search::search_iterative(&mut board, depth) else { return false };
+    best_moves.is_none_or(|moves| moves.contains(&m)) && avoid_moves.is_none_or(|moves| !moves.contains(&m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_epd() {
+        assert!(run_epd("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"back rank mate\";", 2));
+        // Black's queen is hanging, and leaving it there by castling is the mistake to avoid
+        let hanging = "r1b1kb1r/pppp1ppp/5n2/4p3/2BnP2q/2N2N2/PPPP1PPP/R1BQK2R w KQkq -";
+        assert!(run_epd(&format!("{} bm Nxh4; id \"free queen\";", hanging), 3));
+        assert!(run_epd(&format!("{} am O-O;", hanging), 3));
+
+        assert!(!run_epd(&format!("{} bm O-O;", hanging), 3));
+        assert!(!run_epd(&format!("{} id \"no moves to check\";", hanging), 3));
+        assert!(!run_epd(&format!("{} bm Qd8;", hanging), 3));
+        assert!(!run_epd("not a position bm e4;", 3));
+    }
+}
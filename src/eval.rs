@@ -0,0 +1,415 @@
+use crate::board::{BOARD_LENGTH, Chessman, Coordinate, MailboxBoard, NUM_TILES, Piece, Team};
+use crate::movegen;
+use crate::tables::KING_ATTACKS;
+
+// Piece-square tables from White's point of view, laid out the way the board looks with rank 8 on the first row.
+// They're mirrored for Black. The values follow the well known "simplified evaluation function".
+
+/// positional bonus for pawns, pushing toward promotion and holding the center
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; NUM_TILES] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+/// positional bonus for knights, which are much better in the center
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; NUM_TILES] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+/// positional bonus for bishops, avoiding corners and edges
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; NUM_TILES] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+/// positional bonus for rooks, liking the seventh rank and central files
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; NUM_TILES] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+/// positional bonus for queens, mildly central
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; NUM_TILES] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+/// positional bonus for kings, staying tucked behind the pawns
+#[rustfmt::skip]
+const KING_TABLE: [i32; NUM_TILES] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+/// positional bonus for kings once most pieces are gone, heading for the center to join in
+#[rustfmt::skip]
+const KING_ENDGAME_TABLE: [i32; NUM_TILES] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// Sums the material on the board in centipawns, positive when White is ahead
+pub fn material(board: &MailboxBoard) -> i32 {
+    Coordinate::all()
+        .filter_map(|square| board.get_tile(square).data())
+        .map(|(team, chessman)| match team {
+            Team::White => chessman.value(),
+            Team::Black => -chessman.value(),
+        })
+        .sum()
+}
+
+/// the game phase with every knight, bishop, rook and queen still on the board
+const MAX_PHASE: i32 = 24;
+
+/// gets how much a piece counts toward the game phase, where pawns and kings don't count at all
+fn phase_weight(chessman: Chessman) -> i32 {
+    match chessman {
+        Chessman::Queen => 4,
+        Chessman::Rook => 2,
+        Chessman::Bishop | Chessman::Knight => 1,
+        Chessman::Pawn | Chessman::King => 0,
+    }
+}
+
+/// works out how far the game is from the endgame, from [`MAX_PHASE`] in the opening down to 0 with only
+/// kings and pawns left
+fn game_phase(board: &MailboxBoard) -> i32 {
+    let phase: i32 = Coordinate::all()
+        .filter_map(|square| board.get_tile(square).data())
+        .map(|(_, chessman)| phase_weight(chessman))
+        .sum();
+    // promotions can take it past the starting material
+    phase.min(MAX_PHASE)
+}
+
+/// gets the positional bonus for a piece of a team on a square, from the endgame tables if asked
+fn square_bonus(team: Team, chessman: Chessman, square: Coordinate, endgame: bool) -> i32 {
+    let table = match chessman {
+        Chessman::King if endgame => &KING_ENDGAME_TABLE,
+        Chessman::King => &KING_TABLE,
+        Chessman::Queen => &QUEEN_TABLE,
+        Chessman::Bishop => &BISHOP_TABLE,
+        Chessman::Knight => &KNIGHT_TABLE,
+        Chessman::Rook => &ROOK_TABLE,
+        Chessman::Pawn => &PAWN_TABLE,
+    };
+
+    // the tables put rank 8 first, which is the far side for White and the near side for Black
+    let row = match team {
+        Team::White => BOARD_LENGTH - 1 - square.rank() as usize,
+        Team::Black => square.rank() as usize,
+    };
+
+    table[row * BOARD_LENGTH + square.file() as usize]
+}
+
+/// penalty for each file beside or in front of the king without one of its own pawns sheltering it
+const MISSING_SHIELD_PENALTY: i32 = 15;
+
+/// penalty for each square around the king, or the king's own square, that the opponent attacks
+const KING_ZONE_ATTACK_PENALTY: i32 = 10;
+
+/// works out how exposed a team's king is, as a penalty in centipawns that's zero for a sheltered king
+///
+/// A file next to or in front of the king counts as shielded when one of the team's pawns stands one or two
+/// squares ahead of the king on it.
+fn king_safety(board: &MailboxBoard, team: Team) -> i32 {
+    let Some(king) = board.king_square(team) else { return 0 };
+    let forward = match team {
+        Team::White => 1,
+        Team::Black => -1,
+    };
+    let pawn = Piece::new(Some((team, Chessman::Pawn)));
+
+    let mut penalty = 0;
+    for d_file in [-1, 0, 1] {
+        if king.offset(0, d_file).is_none() {
+            continue;
+        }
+        let shielded = [forward, 2 * forward]
+            .into_iter()
+            .any(|ahead| king.offset(ahead, d_file).is_some_and(|square| board.get_tile(square) == pawn));
+        if !shielded {
+            penalty += MISSING_SHIELD_PENALTY;
+        }
+    }
+
+    let zone = KING_ATTACKS[king.index() as usize] | 1 << king.index();
+    penalty += Coordinate::all()
+        .filter(|square| zone & (1 << square.index()) != 0 && board.is_attacked(*square, team.opponent()))
+        .count() as i32
+        * KING_ZONE_ATTACK_PENALTY;
+
+    penalty
+}
+
+/// penalty for each pawn on a file beyond the first of its team's pawns there
+const DOUBLED_PAWN_PENALTY: i32 = 20;
+
+/// penalty for a pawn with none of its team's pawns on the files next to it
+const ISOLATED_PAWN_PENALTY: i32 = 15;
+
+/// bonus for a passed pawn, indexed by how many ranks it has come from its team's back rank
+const PASSED_PAWN_BONUS: [i32; BOARD_LENGTH] = [0, 10, 15, 25, 45, 75, 120, 0];
+
+/// scores a team's pawns for doubled, isolated and passed pawns, in centipawns
+///
+/// A pawn is passed when no enemy pawn stands ahead of it on its own file or the files next to it.
+fn pawn_structure(board: &MailboxBoard, team: Team) -> i32 {
+    let pawn = Piece::new(Some((team, Chessman::Pawn)));
+    let enemy_pawn = Piece::new(Some((team.opponent(), Chessman::Pawn)));
+
+    // how many of the team's pawns are on each file
+    let mut files = [0; BOARD_LENGTH];
+    for square in Coordinate::all().filter(|&square| board.get_tile(square) == pawn) {
+        files[square.file() as usize] += 1;
+    }
+    let on_file = |file: i8| usize::try_from(file).ok().and_then(|file| files.get(file)).copied().unwrap_or(0);
+
+    let mut score = 0;
+    for square in Coordinate::all().filter(|&square| board.get_tile(square) == pawn) {
+        let file = square.file() as i8;
+        if on_file(file - 1) == 0 && on_file(file + 1) == 0 {
+            score -= ISOLATED_PAWN_PENALTY;
+        }
+
+        let ranks_ahead = |enemy: Coordinate| match team {
+            Team::White => enemy.rank() > square.rank(),
+            Team::Black => enemy.rank() < square.rank(),
+        };
+        let passed = !Coordinate::all().any(|enemy| {
+            board.get_tile(enemy) == enemy_pawn && ranks_ahead(enemy) && (enemy.file() as i8 - file).abs() <= 1
+        });
+        if passed {
+            let advanced = match team {
+                Team::White => square.rank() as usize,
+                Team::Black => BOARD_LENGTH - 1 - square.rank() as usize,
+            };
+            score += PASSED_PAWN_BONUS[advanced];
+        }
+    }
+
+    score - files.iter().map(|&count| (count - 1).max(0) * DOUBLED_PAWN_PENALTY).sum::<i32>()
+}
+
+/// bonus for each pseudo-legal move a team has
+const MOBILITY_BONUS: i32 = 4;
+
+/// scores how active a team's pieces are by how many pseudo-legal moves they have, in centipawns
+fn mobility(board: &MailboxBoard, team: Team) -> i32 {
+    movegen::count_team_moves(board, team) as i32 * MOBILITY_BONUS
+}
+
+/// Evaluates the board in centipawns, positive when White is better
+///
+/// This is material plus a positional bonus for where each piece stands, less a penalty for a king left without
+/// pawns in front of it or with enemy pieces bearing down on it, and scores for the shape of each side's pawns and
+/// how many moves each side has.
+///
+/// The positional bonuses and king safety are scored twice, once for the middlegame and once for the endgame,
+/// and blended by [`game_phase`], so a king is kept sheltered while the pieces are on and brought out once
+/// they've come off.
+pub fn evaluate(board: &MailboxBoard) -> i32 {
+    let mut midgame = 0;
+    let mut endgame = 0;
+
+    for square in Coordinate::all() {
+        if let Some((team, chessman)) = board.get_tile(square).data() {
+            let sign = match team {
+                Team::White => 1,
+                Team::Black => -1,
+            };
+            midgame += sign * (chessman.value() + square_bonus(team, chessman, square, false));
+            endgame += sign * (chessman.value() + square_bonus(team, chessman, square, true));
+        }
+    }
+    // with few pieces left there's little to attack a king with, so its shelter stops mattering
+    midgame += king_safety(board, Team::Black) - king_safety(board, Team::White);
+
+    let phase = game_phase(board);
+    let mut score = (midgame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE;
+
+    score += pawn_structure(board, Team::White) - pawn_structure(board, Team::Black);
+    score + mobility(board, Team::White) - mobility(board, Team::Black)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn test_material() {
+        let mut board = MailboxBoard::starting_position();
+        assert_eq!(material(&board), 0);
+
+        board.clear_tile(Coordinate::from_algebraic("d8").unwrap());
+        assert_eq!(material(&board), 900);
+
+        board.clear_tile(Coordinate::from_algebraic("a1").unwrap());
+        board.clear_tile(Coordinate::from_algebraic("b1").unwrap());
+        assert_eq!(material(&board), 900 - 500 - 320);
+
+        assert_eq!(material(&MailboxBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap()), 0);
+        assert_eq!(material(&MailboxBoard::from_fen("4k3/pp6/8/8/8/8/8/2B1K3 w - - 0 1").unwrap()), 130);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // the starting position is symmetric
+        assert_eq!(evaluate(&MailboxBoard::starting_position()), 0);
+
+        let center = MailboxBoard::from_fen("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1").unwrap();
+        let corner = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+        assert_eq!(material(&center), material(&corner));
+        assert!(evaluate(&center) > evaluate(&corner));
+
+        // same for Black, whose better position scores lower
+        let center = MailboxBoard::from_fen("4k3/8/8/3n4/8/8/8/4K3 w - - 0 1").unwrap();
+        let corner = MailboxBoard::from_fen("n3k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(evaluate(&center) < evaluate(&corner));
+
+        // advanced pawns are worth more
+        let pushed = MailboxBoard::from_fen("4k3/8/4P3/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let home = MailboxBoard::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert!(evaluate(&pushed) > evaluate(&home));
+    }
+
+    #[test]
+    fn test_king_safety() {
+        let sheltered = MailboxBoard::from_fen("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+        let exposed = MailboxBoard::from_fen("6k1/5ppp/8/8/6P1/8/5P1P/6K1 w - - 0 1").unwrap();
+        assert_eq!(king_safety(&sheltered, Team::White), 0);
+        assert_eq!(king_safety(&exposed, Team::White), MISSING_SHIELD_PENALTY);
+        assert!(evaluate(&exposed) < evaluate(&sheltered));
+
+        // the same broken shield on Black's side is good for White
+        assert!(evaluate(&exposed.mirror()) > evaluate(&sheltered.mirror()));
+
+        // a queen bearing down on the open file adds to the danger
+        let attacked = MailboxBoard::from_fen("6k1/5ppp/8/2q5/6P1/8/5P1P/6K1 w - - 0 1").unwrap();
+        assert!(king_safety(&attacked, Team::White) > king_safety(&exposed, Team::White));
+        assert_eq!(king_safety(&MailboxBoard::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap(), Team::White), 0);
+    }
+
+    #[test]
+    fn test_pawn_structure() {
+        // Black's pawns stop any of White's from being passed
+        let healthy = MailboxBoard::from_fen("4k3/ppp5/8/8/8/8/PPP5/4K3 w - - 0 1").unwrap();
+        let doubled = MailboxBoard::from_fen("4k3/ppp5/8/8/8/1P6/PP6/4K3 w - - 0 1").unwrap();
+        assert_eq!(pawn_structure(&healthy, Team::White), 0);
+        assert_eq!(pawn_structure(&doubled, Team::White), -DOUBLED_PAWN_PENALTY);
+        assert!(evaluate(&doubled) < evaluate(&healthy));
+
+        let isolated = MailboxBoard::from_fen("4k3/ppp5/8/8/8/8/P1P5/4K3 w - - 0 1").unwrap();
+        assert_eq!(pawn_structure(&isolated, Team::White), -2 * ISOLATED_PAWN_PENALTY);
+
+        // nothing can stop the pawn on the 6th rank, unlike with a pawn on the next file waiting for it
+        let passed = MailboxBoard::from_fen("4k3/8/4P3/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let blocked = MailboxBoard::from_fen("4k3/3p4/4P3/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(pawn_structure(&passed, Team::White), PASSED_PAWN_BONUS[5] - ISOLATED_PAWN_PENALTY);
+        assert!(PASSED_PAWN_BONUS[5] >= 50);
+        assert!(pawn_structure(&blocked, Team::White) < pawn_structure(&passed, Team::White));
+        assert_eq!(pawn_structure(&passed.mirror(), Team::Black), pawn_structure(&passed, Team::White));
+    }
+
+    #[test]
+    fn test_mobility() {
+        // the same bishop and pawn, with the bishop out in the open or shut in behind the pawn
+        let open = MailboxBoard::from_fen("4k3/8/8/8/3B4/8/6P1/4K3 w - - 0 1").unwrap();
+        let cramped = MailboxBoard::from_fen("4k3/8/8/8/8/8/6P1/4K2B w - - 0 1").unwrap();
+        assert_eq!(material(&open), material(&cramped));
+        assert!(mobility(&open, Team::White) > mobility(&cramped, Team::White));
+        assert_eq!(mobility(&open, Team::Black), mobility(&cramped, Team::Black));
+        assert!(evaluate(&open) > evaluate(&cramped));
+
+        assert_eq!(mobility(&MailboxBoard::starting_position(), Team::White), 20 * MOBILITY_BONUS);
+    }
+
+    #[test]
+    fn test_game_phase() {
+        assert_eq!(game_phase(&MailboxBoard::starting_position()), MAX_PHASE);
+        assert_eq!(game_phase(&MailboxBoard::from_fen("4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1").unwrap()), 0);
+        assert_eq!(game_phase(&MailboxBoard::from_fen("3qk3/8/8/8/8/8/8/R3KN2 w - - 0 1").unwrap()), 7);
+        // extra queens don't push it past the opening
+        assert_eq!(game_phase(&MailboxBoard::from_fen("QQQQkQQQ/8/8/8/8/8/8/4K3 w - - 0 1").unwrap()), MAX_PHASE);
+    }
+
+    #[test]
+    fn test_tapered_king() {
+        // in the endgame the king belongs in the center
+        let home = MailboxBoard::from_fen("4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1").unwrap();
+        let central = MailboxBoard::from_fen("4k3/pppp4/8/8/4K3/8/PPPP4/8 w - - 0 1").unwrap();
+        assert!(evaluate(&central) > evaluate(&home));
+
+        // but with every piece still on, it's safer at home
+        let home = MailboxBoard::starting_position();
+        let central = MailboxBoard::from_fen("rnbqkbnr/pppppppp/8/8/4K3/8/PPPPPPPP/RNBQ1BNR w kq - 0 1").unwrap();
+        assert!(evaluate(&central) < evaluate(&home));
+
+        let e4 = Coordinate::from_algebraic("e4").unwrap();
+        assert!(square_bonus(Team::White, Chessman::King, e4, true) > square_bonus(Team::White, Chessman::King, e4, false));
+    }
+
+    #[test]
+    fn test_evaluate_mirror() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/3n4/8/8/8/4K3 w - - 0 1",
+        ] {
+            let board = MailboxBoard::from_fen(fen).unwrap();
+            assert_eq!(evaluate(&board), -evaluate(&board.mirror()), "{}", fen);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use core::convert::TryFrom;
+use crate::board::{Board, BOARD_LENGTH, CastleSide, Coordinate, MailboxBoard, Piece, Team};
+
+/// Errors from parsing FEN
+#[derive(Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// there were more than 8 ranks in the piece placement
+    TooManyRanks,
+    /// there were less than 8 ranks in the piece placement
+    TooFewRanks,
+    /// a character that isn't a piece letter or digit
+    InvalidCharacter(char),
+    /// the rank, counting from 0 for rank 1, doesn't describe exactly 8 files
+    BadRankLength(u8),
+    /// the active color wasn't `w` or `b`
+    InvalidActiveColor,
+    /// the castling availability had something other than `KQkq` or `-`
+    InvalidCastling(char),
+    /// the en passant target wasn't `-` or a square on rank 3 or 6
+    InvalidEnPassant,
+    /// the halfmove clock wasn't a number
+    InvalidHalfmoveClock,
+    /// the fullmove number wasn't a number
+    InvalidFullmoveNumber,
+}
+
+impl MailboxBoard {
+    /// Creates a board from a FEN string
+    ///
+    /// Anything after the six fields is ignored. Missing fields fall back to White to move, no castling, no en
+    /// passant, a halfmove clock of 0 and a fullmove number of 1.
+    pub fn from_fen(fen: &str) -> Result<MailboxBoard, FenError> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields.next().unwrap_or("");
+
+        let mut board = MailboxBoard::empty();
+        let mut ranks = 0;
+
+        for (row, rank_str) in placement.split('/').enumerate() {
+            if row >= BOARD_LENGTH {
+                return Err(FenError::TooManyRanks);
+            }
+            ranks += 1;
+
+            // FEN starts from rank 8
+            let rank = (BOARD_LENGTH - 1 - row) as u8;
+            let mut file = 0;
+
+            for c in rank_str.chars() {
+                if let Some(skip) = c.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                    file += skip as usize;
+                } else {
+                    let piece = Piece::try_from(c).map_err(|_| FenError::InvalidCharacter(c))?;
+                    if file >= BOARD_LENGTH {
+                        return Err(FenError::BadRankLength(rank));
+                    }
+
+                    let coord = Coordinate::try_from((rank, file as u8)).map_err(|_| FenError::BadRankLength(rank))?;
+                    board.set_tile(coord, piece);
+                    file += 1;
+                }
+            }
+
+            if file != BOARD_LENGTH {
+                return Err(FenError::BadRankLength(rank));
+            }
+        }
+
+        if ranks < BOARD_LENGTH {
+            return Err(FenError::TooFewRanks);
+        }
+
+        match fields.next() {
+            Some("w") | None => board.set_side_to_move(Team::White),
+            Some("b") => board.set_side_to_move(Team::Black),
+            Some(_) => return Err(FenError::InvalidActiveColor),
+        }
+
+        match fields.next() {
+            Some("-") | None => {}
+            Some(castling) => {
+                for c in castling.chars() {
+                    let (team, side) = match c {
+                        'K' => (Team::White, CastleSide::KingSide),
+                        'Q' => (Team::White, CastleSide::QueenSide),
+                        'k' => (Team::Black, CastleSide::KingSide),
+                        'q' => (Team::Black, CastleSide::QueenSide),
+                        _ => return Err(FenError::InvalidCastling(c)),
+                    };
+                    board.grant_castle(team, side);
+                }
+            }
+        }
+
+        match fields.next() {
+            Some("-") | None => {}
+            Some(square) => {
+                let square = Coordinate::from_algebraic(square).map_err(|_| FenError::InvalidEnPassant)?;
+                if square.rank() != 2 && square.rank() != 5 {
+                    return Err(FenError::InvalidEnPassant);
+                }
+                board.set_en_passant(Some(square));
+            }
+        }
+
+        if let Some(halfmove_clock) = fields.next() {
+            board.set_halfmove_clock(halfmove_clock.parse().map_err(|_| FenError::InvalidHalfmoveClock)?);
+        }
+
+        if let Some(fullmove_number) = fields.next() {
+            board.set_fullmove_number(fullmove_number.parse().map_err(|_| FenError::InvalidFullmoveNumber)?);
+        }
+
+        Ok(board)
+    }
+
+    /// Writes the FEN for this board, with all six fields
+    #[cfg(feature = "std")]
+    pub fn to_fen(&self) -> String {
+        let active_color = match self.side_to_move() {
+            Team::White => 'w',
+            Team::Black => 'b',
+        };
+
+        let mut castling = String::new();
+        for (team, side, c) in [
+            (Team::White, CastleSide::KingSide, 'K'),
+            (Team::White, CastleSide::QueenSide, 'Q'),
+            (Team::Black, CastleSide::KingSide, 'k'),
+            (Team::Black, CastleSide::QueenSide, 'q'),
+        ] {
+            if self.can_castle(team, side) {
+                castling.push(c);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant() {
+            Some(square) => square.to_string(),
+            None => String::from("-"),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            self.to_fen_placement(),
+            active_color,
+            castling,
+            en_passant,
+            self.halfmove_clock(),
+            self.fullmove_number()
+        )
+    }
+
+    /// Writes the piece placement field of FEN for this board
+    #[cfg(feature = "std")]
+    pub fn to_fen_placement(&self) -> String {
+        let mut placement = String::new();
+
+        for rank in (0..BOARD_LENGTH as u8).rev() {
+            let mut empty = 0;
+
+            for file in 0..BOARD_LENGTH as u8 {
+                let coord = Coordinate::try_from((rank, file)).expect("rank and file are in bounds");
+
+                match self.get_tile(coord).to_fen_char() {
+                    Some(c) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(c);
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        placement
+    }
+}
+
+/// Boards serialize as their FEN, see `to_fen` for what that leaves out
+#[cfg(feature = "serde")]
+impl serde::Serialize for MailboxBoard {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_fen())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MailboxBoard {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fen = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        MailboxBoard::from_fen(&fen).map_err(|error| serde::de::Error::custom(format!("invalid FEN: {:?}", error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Chessman, NUM_TILES};
+
+    const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn test_from_fen_starting_position() {
+        let board = MailboxBoard::from_fen(STARTING_FEN).expect("from_fen failed");
+        let expected = MailboxBoard::starting_position();
+
+        for index in 0..NUM_TILES as u8 {
+            let coord = Coordinate::try_from(index).expect("try_from failed");
+            assert_eq!(board.get_tile(coord), expected.get_tile(coord));
+        }
+    }
+
+    #[test]
+    fn test_from_fen_errors() {
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8/8").err(), Some(FenError::TooManyRanks));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8").err(), Some(FenError::TooFewRanks));
+        assert_eq!(MailboxBoard::from_fen("").err(), Some(FenError::BadRankLength(7)));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/7x").err(), Some(FenError::InvalidCharacter('x')));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/09").err(), Some(FenError::InvalidCharacter('0')));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/7").err(), Some(FenError::BadRankLength(0)));
+        assert_eq!(MailboxBoard::from_fen("8/ppppppppp/8/8/8/8/8/8").err(), Some(FenError::BadRankLength(6)));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/44p").err(), Some(FenError::BadRankLength(0)));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8 x").err(), Some(FenError::InvalidActiveColor));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8 w KQx").err(), Some(FenError::InvalidCastling('x')));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8 w - e4").err(), Some(FenError::InvalidEnPassant));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8 w - x3").err(), Some(FenError::InvalidEnPassant));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8 w - - x").err(), Some(FenError::InvalidHalfmoveClock));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8 w - - -1").err(), Some(FenError::InvalidHalfmoveClock));
+        assert_eq!(MailboxBoard::from_fen("8/8/8/8/8/8/8/8 w - - 0 x").err(), Some(FenError::InvalidFullmoveNumber));
+    }
+
+    #[test]
+    fn test_fen_halfmove_clock() {
+        let board = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 37 60").expect("from_fen failed");
+        assert_eq!(board.halfmove_clock(), 37);
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 37 60");
+
+        let board = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 100 90").expect("from_fen failed");
+        assert!(board.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_fen_en_passant() {
+        let fen = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 1";
+        let board = MailboxBoard::from_fen(fen).expect("from_fen failed");
+        assert_eq!(board.en_passant(), Coordinate::from_algebraic("d6").ok());
+        assert_eq!(board.to_fen(), fen);
+
+        let board = MailboxBoard::from_fen(STARTING_FEN).expect("from_fen failed");
+        assert_eq!(board.en_passant(), None);
+    }
+
+    #[test]
+    fn test_fen_castling() {
+        let all = MailboxBoard::from_fen(STARTING_FEN).expect("from_fen failed");
+        let none = MailboxBoard::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1").expect("from_fen failed");
+        let some = MailboxBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1").expect("from_fen failed");
+
+        for team in Team::all() {
+            for side in [CastleSide::KingSide, CastleSide::QueenSide] {
+                assert!(all.can_castle(team, side));
+                assert!(!none.can_castle(team, side));
+            }
+        }
+
+        assert!(some.can_castle(Team::White, CastleSide::KingSide));
+        assert!(!some.can_castle(Team::White, CastleSide::QueenSide));
+        assert!(!some.can_castle(Team::Black, CastleSide::KingSide));
+        assert!(some.can_castle(Team::Black, CastleSide::QueenSide));
+
+        assert_eq!(all.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(none.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1");
+        assert_eq!(some.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1");
+        assert_eq!(MailboxBoard::starting_position().to_fen(), all.to_fen());
+    }
+
+    #[test]
+    fn test_from_fen_active_color() {
+        let white = MailboxBoard::from_fen(STARTING_FEN).expect("from_fen failed");
+        let black = MailboxBoard::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").expect("from_fen failed");
+
+        assert_eq!(white.side_to_move(), Team::White);
+        assert_eq!(black.side_to_move(), Team::Black);
+
+        let white_moves = white.get_moves();
+        let black_moves = black.get_moves();
+        assert_eq!(white_moves.len(), 20);
+        assert_eq!(black_moves.len(), 20);
+        assert!(white_moves.iter().all(|m| m.piece().data().map(|(team, _)| team) == Some(Team::White)));
+        assert!(black_moves.iter().all(|m| m.piece().data().map(|(team, _)| team) == Some(Team::Black)));
+    }
+
+    #[test]
+    fn test_to_fen_placement() {
+        assert_eq!(MailboxBoard::starting_position().to_fen_placement(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+        assert_eq!(MailboxBoard::empty().to_fen_placement(), "8/8/8/8/8/8/8/8");
+
+        let mut board = MailboxBoard::empty();
+        board.set_tile(Coordinate::try_from(3u8).unwrap(), Piece::new(Some((Team::White, Chessman::King))));
+        board.set_tile(Coordinate::try_from(63u8).unwrap(), Piece::new(Some((Team::Black, Chessman::King))));
+        assert_eq!(board.to_fen_placement(), "7k/8/8/8/8/8/8/3K4");
+    }
+
+    #[test]
+    fn test_fen_placement_round_trip() {
+        let letters = ['K', 'Q', 'B', 'N', 'R', 'P', 'k', 'q', 'b', 'n', 'r', 'p'];
+        // small LCG so the arrangements are random looking but repeatable
+        let mut seed: u32 = 0x2545_f491;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as usize
+        };
+
+        let mut boards = vec![MailboxBoard::starting_position()];
+        for _ in 0..20 {
+            let mut board = MailboxBoard::empty();
+            for index in 0..NUM_TILES as u8 {
+                // leave roughly half the tiles empty so runs of digits show up
+                if next() % 2 == 0 {
+                    let piece = Piece::try_from(letters[next() % letters.len()]).unwrap();
+                    board.set_tile(Coordinate::try_from(index).unwrap(), piece);
+                }
+            }
+            boards.push(board);
+        }
+
+        for board in boards {
+            let placement = board.to_fen_placement();
+            let parsed = MailboxBoard::from_fen(&placement).expect("from_fen failed");
+
+            for index in 0..NUM_TILES as u8 {
+                let coord = Coordinate::try_from(index).unwrap();
+                assert_eq!(parsed.get_tile(coord), board.get_tile(coord), "{}", placement);
+            }
+            assert_eq!(parsed.to_fen_placement(), placement);
+        }
+    }
+
+    #[test]
+    fn test_fen_round_trip() {
+        for fen in [
+            STARTING_FEN,
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 1",
+            "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq - 0 17",
+            "rnbqkbnr/pp1ppppp/8/8/2pPP3/8/PPP2PPP/RNBQKBNR b Kk d3 0 3",
+            "4k2r/8/8/8/8/8/8/R3K3 w Qk - 12 41",
+        ] {
+            let board = MailboxBoard::from_fen(fen).expect("from_fen failed");
+            assert_eq!(board.to_fen(), fen);
+
+            let parsed = MailboxBoard::from_fen(&board.to_fen()).expect("from_fen failed");
+            assert!(parsed == board, "{}", fen);
+            assert_eq!(parsed.halfmove_clock(), board.halfmove_clock());
+            assert_eq!(parsed.fullmove_number(), board.fullmove_number());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let board = MailboxBoard::starting_position();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, format!("\"{}\"", board.to_fen()));
+
+        let back: MailboxBoard = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.to_fen(), board.to_fen());
+        assert_eq!(back.zobrist_hash(), board.zobrist_hash());
+
+        assert!(serde_json::from_str::<MailboxBoard>("\"not a fen\"").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_pieces_and_squares() {
+        let square = Coordinate::from_algebraic("e4").unwrap();
+        assert_eq!(serde_json::to_string(&square).unwrap(), "28");
+        assert_eq!(serde_json::from_str::<Coordinate>("28").unwrap(), square);
+        assert!(serde_json::from_str::<Coordinate>("64").is_err());
+
+        let piece = Piece::new(Some((Team::Black, Chessman::Knight)));
+        let json = serde_json::to_string(&piece).unwrap();
+        assert_eq!(json, r#"["Black","Knight"]"#);
+        assert_eq!(serde_json::from_str::<Piece>(&json).unwrap(), piece);
+        assert_eq!(serde_json::from_str::<Piece>("null").unwrap(), Piece::new(None));
+    }
+}
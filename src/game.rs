@@ -0,0 +1,288 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::fmt;
+
+use crate::board::{MailboxBoard, Move, Team, Undo};
+use crate::movegen::GameStatus;
+
+/// Errors from playing a move in a game
+#[derive(Debug, PartialEq, Eq)]
+pub enum GameError {
+    /// the move isn't one of the legal moves in the current position
+    IllegalMove,
+    /// the game has already been decided by a resignation or an agreed draw
+    GameOver,
+    /// there's no draw offer from the opponent to accept
+    NoDrawOffer,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::IllegalMove => write!(f, "illegal move"),
+            GameError::GameOver => write!(f, "the game is over"),
+            GameError::NoDrawOffer => write!(f, "no draw has been offered"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GameError {}
+
+/// How a game was decided away from the board, by a resignation or an agreed draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+/// A game in progress: a board along with every move played on it, which can be taken back
+#[derive(Clone)]
+pub struct Game {
+    board: MailboxBoard,
+    moves: Vec<Move>,
+    /// one for each of `moves`, for taking them back
+    undos: Vec<Undo>,
+    /// set once a player resigns or a draw is agreed, after which no more moves can be played
+    result: Option<GameResult>,
+    /// the team whose draw offer is waiting for an answer
+    draw_offer: Option<Team>,
+}
+
+impl Game {
+    /// Starts a game from the starting position
+    pub fn new() -> Self {
+        Game::from_board(MailboxBoard::starting_position())
+    }
+
+    /// Starts a game from some position, with no moves played yet
+    pub fn from_board(board: MailboxBoard) -> Self {
+        Game { board, moves: Vec::new(), undos: Vec::new(), result: None, draw_offer: None }
+    }
+
+    /// gets the current position
+    pub fn board(&self) -> &MailboxBoard {
+        &self.board
+    }
+
+    /// gets the moves played so far, oldest first
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// gets how the game was decided by resignation or agreement, or None if it hasn't been
+    ///
+    /// Checkmate and stalemate aren't included, those come from [`status`](Self::status).
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// Plays a move, as long as it's legal in the current position and the game hasn't been decided
+    ///
+    /// Playing on turns down any draw the opponent offered.
+    pub fn push_move(&mut self, m: Move) -> Result<(), GameError> {
+        if self.result.is_some() {
+            return Err(GameError::GameOver);
+        }
+        if !self.board.legal_moves().contains(&m) {
+            return Err(GameError::IllegalMove);
+        }
+
+        let mover = self.board.side_to_move();
+        if self.draw_offer.is_some_and(|offered_by| offered_by != mover) {
+            self.draw_offer = None;
+        }
+        self.undos.push(self.board.make_move(m));
+        self.moves.push(m);
+        Ok(())
+    }
+
+    /// Takes back the last move played, returning it, or None if no moves have been played
+    ///
+    /// A decided game stays as it ended, so nothing is taken back once there's a [`result`](Self::result). Any
+    /// draw offer waiting for an answer is withdrawn along with the move.
+    pub fn pop_move(&mut self) -> Option<Move> {
+        if self.result.is_some() {
+            return None;
+        }
+
+        let undo = self.undos.pop()?;
+        self.draw_offer = None;
+        self.board.unmake_move(undo);
+        self.moves.pop()
+    }
+
+    /// Offers a draw on behalf of the side to move, which stands until the opponent accepts it or plays a move
+    pub fn offer_draw(&mut self) -> Result<(), GameError> {
+        if self.result.is_some() {
+            return Err(GameError::GameOver);
+        }
+
+        self.draw_offer = Some(self.board.side_to_move());
+        Ok(())
+    }
+
+    /// Accepts the opponent's draw offer on behalf of the side to move, ending the game in a draw
+    pub fn accept_draw(&mut self) -> Result<(), GameError> {
+        if self.result.is_some() {
+            return Err(GameError::GameOver);
+        }
+        if self.draw_offer != Some(self.board.side_to_move().opponent()) {
+            return Err(GameError::NoDrawOffer);
+        }
+
+        self.result = Some(GameResult::Draw);
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    /// Resigns the game for a team, whether or not it's their move, so the other team wins
+    pub fn resign(&mut self, team: Team) -> Result<(), GameError> {
+        if self.result.is_some() {
+            return Err(GameError::GameOver);
+        }
+
+        self.result = Some(match team {
+            Team::White => GameResult::BlackWins,
+            Team::Black => GameResult::WhiteWins,
+        });
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    /// Generates the legal moves in the current position
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.board.legal_moves()
+    }
+
+    /// Works out whether the game is still going, or has ended in checkmate or stalemate
+    pub fn status(&self) -> GameStatus {
+        self.board.status()
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// plays some moves given in UCI
+    fn play(game: &mut Game, moves: &[&str]) {
+        for uci in moves {
+            let m = game.board().parse_uci(uci).unwrap();
+            game.push_move(m).expect(uci);
+        }
+    }
+
+    #[test]
+    fn test_push_and_pop_moves() {
+        let mut game = Game::new();
+        play(&mut game, &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"]);
+        assert_eq!(game.moves().len(), 5);
+
+        let bishop = game.pop_move().unwrap();
+        assert_eq!(bishop.to_uci(), "f1b5");
+        assert_eq!(game.pop_move().unwrap().to_uci(), "b8c6");
+
+        let mut expected = MailboxBoard::starting_position();
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            expected.make_move(expected.parse_uci(uci).unwrap());
+        }
+        assert!(*game.board() == expected);
+        assert_eq!(game.board().to_fen(), expected.to_fen());
+        assert_eq!(game.moves().len(), 3);
+        assert_eq!(game.board().side_to_move(), Team::Black);
+
+        // the bishop can't go to b5 while it's Black's move
+        assert_eq!(game.push_move(bishop), Err(GameError::IllegalMove));
+        assert_eq!(game.moves().len(), 3);
+
+        for _ in 0..3 {
+            assert!(game.pop_move().is_some());
+        }
+        assert_eq!(game.pop_move(), None);
+        assert!(*game.board() == MailboxBoard::starting_position());
+    }
+
+    #[test]
+    fn test_status() {
+        let mut game = Game::new();
+        assert_eq!(game.status(), GameStatus::Ongoing);
+        assert_eq!(game.legal_moves().len(), 20);
+
+        // fool's mate
+        play(&mut game, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+        assert_eq!(game.status(), GameStatus::Checkmate { winner: Team::Black });
+        assert!(game.legal_moves().is_empty());
+
+        game.pop_move();
+        assert_eq!(game.status(), GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_resign() {
+        let mut game = Game::new();
+        play(&mut game, &["e2e4", "e7e5"]);
+        assert_eq!(game.result(), None);
+
+        assert_eq!(game.resign(Team::White), Ok(()));
+        assert_eq!(game.result(), Some(GameResult::BlackWins));
+
+        let m = game.board().parse_uci("g1f3").unwrap();
+        assert_eq!(game.push_move(m), Err(GameError::GameOver));
+        assert_eq!(game.moves().len(), 2);
+        assert_eq!(game.resign(Team::Black), Err(GameError::GameOver));
+        assert_eq!(game.offer_draw(), Err(GameError::GameOver));
+
+        // the moves can't be taken back either
+        assert_eq!(game.pop_move(), None);
+        assert_eq!(game.moves().len(), 2);
+        assert_eq!(game.result(), Some(GameResult::BlackWins));
+    }
+
+    #[test]
+    fn test_draw_offers() {
+        let mut game = Game::new();
+        assert_eq!(game.accept_draw(), Err(GameError::NoDrawOffer));
+
+        // White offers along with a move, and Black turns it down by playing on
+        game.offer_draw().unwrap();
+        assert_eq!(game.accept_draw(), Err(GameError::NoDrawOffer));
+        play(&mut game, &["e2e4", "e7e5"]);
+        assert_eq!(game.accept_draw(), Err(GameError::NoDrawOffer));
+
+        game.offer_draw().unwrap();
+        play(&mut game, &["g1f3"]);
+        assert_eq!(game.accept_draw(), Ok(()));
+        assert_eq!(game.result(), Some(GameResult::Draw));
+        assert_eq!(game.status(), GameStatus::Ongoing);
+        assert_eq!(game.resign(Team::Black), Err(GameError::GameOver));
+    }
+
+    #[test]
+    fn test_pop_move_withdraws_draw_offer() {
+        let mut game = Game::new();
+        play(&mut game, &["e2e4", "e7e5"]);
+        game.offer_draw().unwrap();
+        play(&mut game, &["g1f3"]);
+
+        // White's move is taken back, and the offer made with it goes too
+        assert!(game.pop_move().is_some());
+        play(&mut game, &["g1f3"]);
+        assert_eq!(game.accept_draw(), Err(GameError::NoDrawOffer));
+
+        game.offer_draw().unwrap();
+        play(&mut game, &["b8c6"]);
+        game.accept_draw().unwrap();
+        assert_eq!(game.pop_move(), None);
+        assert_eq!(game.moves().len(), 4);
+        assert_eq!(game.result(), Some(GameResult::Draw));
+    }
+}
@@ -0,0 +1,32 @@
+//! A chess engine built around a mailbox board
+//!
+//! Without the default `std` feature this builds as `no_std`, using `alloc` for move lists. That leaves out
+//! the `Display` and `Error` impls, the helpers that write strings, and the `epd`, `san`, `pgn` and
+//! `uci` modules.
+//!
+//! The `parallel` feature adds `search::search_parallel`, which uses rayon to search the root moves on several
+//! threads at once.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod bitboard;
+pub mod board;
+pub mod book;
+#[cfg(feature = "std")]
+pub mod epd;
+pub mod eval;
+pub mod fen;
+pub mod game;
+pub mod magic;
+pub mod movegen;
+#[cfg(feature = "std")]
+pub mod pgn;
+#[cfg(feature = "std")]
+pub mod san;
+pub mod search;
+pub mod tables;
+pub mod transposition;
+#[cfg(feature = "std")]
+pub mod uci;
+pub mod zobrist;
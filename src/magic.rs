@@ -0,0 +1,357 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use rand::RngCore;
+use crate::board::{Coordinate, BOARD_LENGTH, NUM_TILES};
+use crate::movegen::{BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
+
+/// multipliers that hash each square's rook blockers into a table index without harmful collisions
+///
+/// These came from `find_rook_magic`, which tries random sparse numbers until every blocker arrangement lands on
+/// a slot holding the same attacks. `SliderTable::build` checks them again when the tables are made.
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; NUM_TILES] = [
+    0x2080002080400010, 0x00c0002001401000, 0x2100110008402002, 0x0880080081041000,
+    0x0200020020041008, 0x2300040008010012, 0x0c00283004008201, 0x0180010000407a80,
+    0x0168800080400020, 0x0010400040201000, 0x1001002001001048, 0x1001002408100100,
+    0x0801000408010012, 0x4001000209000400, 0x08a20004c8020001, 0x2002801145002280,
+    0x0080860021004200, 0x001000c009402002, 0x00b0002004002800, 0x100a808010020800,
+    0x8101010008000410, 0x0244008002000480, 0x0000040010810208, 0x2000020000448534,
+    0x4104400480008033, 0x0000810100204000, 0x0440430900200010, 0x4600240900100100,
+    0x0060080080040080, 0x0001000300080400, 0x0004084400011002, 0x0023040200008041,
+    0x0580050043002080, 0x0400804002802008, 0x0001002001004010, 0x1000200901001000,
+    0x4410800801800c00, 0xa012003806001004, 0x0020100104008802, 0x0004808402000041,
+    0x0010400170898000, 0x0080500020004004, 0x1040408012020020, 0x8010040008004040,
+    0x2001080100110004, 0x0000020004008080, 0x0021010810040002, 0x0800008c43020024,
+    0x0000800021005100, 0x0070201040008080, 0x0000d04282006a00, 0x0010014400080240,
+    0x0001080110050100, 0x0012000810240600, 0x0402000801040200, 0x028100108a004100,
+    0x0050800300102045, 0x8208210040120882, 0x8010600101183441, 0x020b000910006045,
+    0x0241001002480005, 0x0081000400880241, 0x0000009008024124, 0x0048122980410402,
+];
+
+/// multipliers like `ROOK_MAGICS`, for bishops, from `find_bishop_magic`
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; NUM_TILES] = [
+    0x0848020822040013, 0x8010a40085821200, 0x0008008430840822, 0x0808048108040000,
+    0x1304042100008104, 0x5001012010204023, 0x81048801b8200420, 0x200a008084012000,
+    0x0040102001042084, 0x840a505042428020, 0x0000700102202920, 0x44101c0c10800002,
+    0x0040040422000000, 0x0180020802090202, 0x4020020811041202, 0x000104308c042000,
+    0x4140661002424400, 0x0028012008010460, 0x0188062102002a00, 0x0014004840102008,
+    0x0105000290400002, 0x8001022200410400, 0x104a041918013446, 0x008a000082008238,
+    0x04a0060008100430, 0x0008220008820801, 0x2508041208005010, 0x4008080200202020,
+    0x2441001013004000, 0x0030008060407000, 0x4008108000420800, 0x0012021050290100,
+    0x0210080482200500, 0xcc01112048100480, 0x0020402806500440, 0x00048e0080580080,
+    0x0040102020020080, 0x0028010440080807, 0x4601041108008800, 0x8040810e04104200,
+    0x901210110400088a, 0xa003080212081050, 0x00c1004048401004, 0x900000a014400800,
+    0x0008021040405401, 0x4020008206002090, 0x0004190424030100, 0x0424008a02026250,
+    0x8004088250900040, 0x1c00430088a04200, 0x0001020094040001, 0x8040210020880061,
+    0x2010040450442032, 0x0800840850044001, 0x0004040802140004, 0x0004080a04222020,
+    0x8088802110022000, 0x1081a10416114400, 0x0205010a24060820, 0x0000000720411080,
+    0x1008000208430400, 0x580c026028810840, 0x802020441020a110, 0x12c0022401020018,
+];
+
+/// checks whether a rank and file are on the board
+fn on_board(rank: i8, file: i8) -> bool {
+    rank >= 0 && rank < BOARD_LENGTH as i8 && file >= 0 && file < BOARD_LENGTH as i8
+}
+
+/// walks each ray from a square, stopping on the first occupied square
+///
+/// This is the slow way to find a slider's attacks, which the tables are filled in with.
+fn ray_attacks(square: usize, occupied: u64, directions: &[(i8, i8); 4]) -> u64 {
+    let mut attacks = 0;
+
+    for &(d_rank, d_file) in directions {
+        let mut rank = (square / BOARD_LENGTH) as i8 + d_rank;
+        let mut file = (square % BOARD_LENGTH) as i8 + d_file;
+
+        while on_board(rank, file) {
+            let bit = 1 << (rank as usize * BOARD_LENGTH + file as usize);
+            attacks |= bit;
+            if occupied & bit != 0 {
+                break;
+            }
+            rank += d_rank;
+            file += d_file;
+        }
+    }
+
+    attacks
+}
+
+/// gets the squares whose occupancy can change a slider's attacks from a square
+///
+/// The last square of each ray is left out, since a slider reaches it whether or not something is there.
+fn blocker_mask(square: usize, directions: &[(i8, i8); 4]) -> u64 {
+    let mut mask = 0;
+
+    for &(d_rank, d_file) in directions {
+        let mut rank = (square / BOARD_LENGTH) as i8 + d_rank;
+        let mut file = (square % BOARD_LENGTH) as i8 + d_file;
+
+        while on_board(rank + d_rank, file + d_file) {
+            mask |= 1 << (rank as usize * BOARD_LENGTH + file as usize);
+            rank += d_rank;
+            file += d_file;
+        }
+    }
+
+    mask
+}
+
+/// what's needed to look up the attacks of a slider on one square
+#[derive(Debug, Clone, Copy)]
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    /// gets where the attacks for an occupancy are kept in the table
+    fn index(&self, occupied: u64) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// tries random multipliers until one sends every arrangement of a square's blockers to a slot for its attacks
+///
+/// Two arrangements may share a slot only if they leave the slider the same attacks. Candidates are the AND of
+/// three random numbers, since multipliers with few bits set work far more often.
+fn find_magic(square: usize, directions: &[(i8, i8); 4], rng: &mut impl RngCore) -> u64 {
+    let mask = blocker_mask(square, directions);
+    let shift = 64 - mask.count_ones();
+
+    // every subset of the mask with its attacks, walked the same way as in `SliderTable::build`
+    let mut subsets = Vec::new();
+    let mut subset: u64 = 0;
+    loop {
+        subsets.push((subset, ray_attacks(square, subset, directions)));
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+
+    // a slider always attacks something, so an empty slot is one that hasn't been filled yet
+    let mut slots = vec![0; subsets.len()];
+    loop {
+        let magic = rng.next_u64() & rng.next_u64() & rng.next_u64();
+        // a multiplier that leaves few bits in the top byte spreads the blockers over too few slots
+        if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+            continue;
+        }
+
+        slots.fill(0);
+        let fits = subsets.iter().all(|&(subset, attacks)| {
+            let slot = &mut slots[(subset.wrapping_mul(magic) >> shift) as usize];
+            let fits = *slot == 0 || *slot == attacks;
+            *slot = attacks;
+            fits
+        });
+        if fits {
+            return magic;
+        }
+    }
+}
+
+/// Finds a multiplier for looking up a rook's attacks from a square, like the ones the tables are built with
+///
+/// Each call can give a different one, depending on the random number generator.
+pub fn find_rook_magic(square: Coordinate, rng: &mut impl RngCore) -> u64 {
+    find_magic(square.index() as usize, &ROOK_DIRECTIONS, rng)
+}
+
+/// Finds a multiplier for looking up a bishop's attacks from a square, see `find_rook_magic`
+pub fn find_bishop_magic(square: Coordinate, rng: &mut impl RngCore) -> u64 {
+    find_magic(square.index() as usize, &BISHOP_DIRECTIONS, rng)
+}
+
+/// the attacks of one kind of slider from every square with every arrangement of blockers
+struct SliderTable {
+    magics: [Magic; NUM_TILES],
+    attacks: Vec<u64>,
+}
+
+impl SliderTable {
+    /// fills in the attacks for every square and arrangement of blockers
+    ///
+    /// Every square gets a slot for each subset of its blocker mask. Panics if a magic sends two subsets with
+    /// different attacks to the same slot.
+    fn build(directions: &[(i8, i8); 4], magics: &[u64; NUM_TILES], size: usize) -> Self {
+        let empty = Magic { mask: 0, magic: 0, shift: 0, offset: 0 };
+        let mut table = SliderTable { magics: [empty; NUM_TILES], attacks: vec![0; size] };
+        let mut offset = 0;
+
+        for (square, &multiplier) in magics.iter().enumerate() {
+            let mask = blocker_mask(square, directions);
+            let magic = Magic { mask, magic: multiplier, shift: 64 - mask.count_ones(), offset };
+
+            // walks through every subset of the mask, ending back on the empty one
+            let mut subset: u64 = 0;
+            loop {
+                let attacks = ray_attacks(square, subset, directions);
+                let slot = magic.index(subset);
+                assert!(table.attacks[slot] == 0 || table.attacks[slot] == attacks, "magic collision");
+                table.attacks[slot] = attacks;
+
+                subset = subset.wrapping_sub(mask) & mask;
+                if subset == 0 {
+                    break;
+                }
+            }
+
+            table.magics[square] = magic;
+            offset += 1 << mask.count_ones();
+        }
+
+        assert_eq!(offset, size, "table size doesn't match the masks");
+        table
+    }
+
+    /// gets the attacks from a square given the occupied squares
+    fn attacks(&self, square: Coordinate, occupied: u64) -> u64 {
+        self.attacks[self.magics[square.index() as usize].index(occupied)]
+    }
+}
+
+/// the tables for both kinds of slider
+struct Tables {
+    rook: SliderTable,
+    bishop: SliderTable,
+}
+
+/// the tables, built the first time they're needed since they're around 800KB
+static TABLES: AtomicPtr<Tables> = AtomicPtr::new(ptr::null_mut());
+
+/// gets the tables, building them if this is the first use
+fn tables() -> &'static Tables {
+    let existing = TABLES.load(Ordering::Acquire);
+    if !existing.is_null() {
+        // SAFETY: the pointer is only ever set to a leaked box, which is never freed
+        return unsafe { &*existing };
+    }
+
+    // a rook has 10 to 12 blockers to a square and a bishop 5 to 9, these are the totals of their subsets
+    let built = Box::into_raw(Box::new(Tables {
+        rook: SliderTable::build(&ROOK_DIRECTIONS, &ROOK_MAGICS, 102_400),
+        bishop: SliderTable::build(&BISHOP_DIRECTIONS, &BISHOP_MAGICS, 5_248),
+    }));
+
+    match TABLES.compare_exchange(ptr::null_mut(), built, Ordering::AcqRel, Ordering::Acquire) {
+        // SAFETY: the box was just leaked and is now shared for good
+        Ok(_) => unsafe { &*built },
+        Err(existing) => {
+            // another thread got there first, so this copy was never shared
+            // SAFETY: `built` came from `Box::into_raw` above and nothing else refers to it
+            drop(unsafe { Box::from_raw(built) });
+            // SAFETY: as for the first load
+            unsafe { &*existing }
+        }
+    }
+}
+
+/// Gets the squares a rook on the square attacks, given the occupied squares as a bitboard
+///
+/// Rays stop on the first occupied square, which is included whichever team holds it. The lookup tables are
+/// built on the first call.
+pub fn rook_attacks(square: Coordinate, occupancy: u64) -> u64 {
+    tables().rook.attacks(square, occupancy)
+}
+
+/// Gets the squares a bishop on the square attacks, see `rook_attacks`
+pub fn bishop_attacks(square: Coordinate, occupancy: u64) -> u64 {
+    tables().bishop.attacks(square, occupancy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// steps an xorshift generator, for reproducible random occupancies
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn test_blocker_masks() {
+        let a1 = Coordinate::from_algebraic("a1").unwrap().index() as usize;
+        let d4 = Coordinate::from_algebraic("d4").unwrap().index() as usize;
+
+        // a2 to a7 and b1 to g1
+        assert_eq!(blocker_mask(a1, &ROOK_DIRECTIONS), 0x0001_0101_0101_017e);
+        assert_eq!(blocker_mask(a1, &ROOK_DIRECTIONS).count_ones(), 12);
+        assert_eq!(blocker_mask(d4, &ROOK_DIRECTIONS).count_ones(), 10);
+        assert_eq!(blocker_mask(d4, &BISHOP_DIRECTIONS).count_ones(), 9);
+        assert_eq!(blocker_mask(a1, &BISHOP_DIRECTIONS).count_ones(), 6);
+    }
+
+    #[test]
+    fn test_magics_match_ray_walk() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+
+        for _ in 0..5_000 {
+            // sparse occupancies look more like real positions, dense ones block every ray early
+            let occupancy = match xorshift(&mut state) % 3 {
+                0 => xorshift(&mut state),
+                1 => xorshift(&mut state) & xorshift(&mut state),
+                _ => xorshift(&mut state) & xorshift(&mut state) & xorshift(&mut state),
+            };
+
+            for square in Coordinate::all() {
+                let index = square.index() as usize;
+                let rook = ray_attacks(index, occupancy, &ROOK_DIRECTIONS);
+                let bishop = ray_attacks(index, occupancy, &BISHOP_DIRECTIONS);
+                assert_eq!(rook_attacks(square, occupancy), rook, "{} {:x}", square, occupancy);
+                assert_eq!(bishop_attacks(square, occupancy), bishop, "{} {:x}", square, occupancy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_magic() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+
+        for name in ["a1", "d4", "h8", "b7"] {
+            let square = Coordinate::from_algebraic(name).unwrap();
+            let index = square.index() as usize;
+
+            // swapping a found magic into the tables still builds them without collisions, and looks up the same attacks
+            let mut rook_magics = ROOK_MAGICS;
+            rook_magics[index] = find_rook_magic(square, &mut rng);
+            let rook = SliderTable::build(&ROOK_DIRECTIONS, &rook_magics, 102_400);
+            let mut bishop_magics = BISHOP_MAGICS;
+            bishop_magics[index] = find_bishop_magic(square, &mut rng);
+            let bishop = SliderTable::build(&BISHOP_DIRECTIONS, &bishop_magics, 5_248);
+
+            for _ in 0..1_000 {
+                let occupancy = xorshift(&mut state) & xorshift(&mut state);
+                assert_eq!(rook.attacks(square, occupancy), ray_attacks(index, occupancy, &ROOK_DIRECTIONS));
+                assert_eq!(bishop.attacks(square, occupancy), ray_attacks(index, occupancy, &BISHOP_DIRECTIONS));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rook_attacks_blocked() {
+        let square = |name: &str| Coordinate::from_algebraic(name).unwrap();
+        let bit = |name: &str| 1u64 << square(name).index();
+
+        // blockers on d6 and f4 stop the rook there, the pieces behind them don't matter
+        let occupancy = bit("d6") | bit("d7") | bit("f4") | bit("h4");
+        let expected = ["d1", "d2", "d3", "d5", "d6", "a4", "b4", "c4", "e4", "f4"]
+            .iter()
+            .fold(0, |bits, name| bits | bit(name));
+        assert_eq!(rook_attacks(square("d4"), occupancy), expected);
+    }
+}
@@ -1,8 +1,5 @@
-// mod board;
-// mod FEN;
+//! Runs the engine over UCI on standard input and output, for chess GUIs to launch
 
-mod board;
-
-fn main() {
-    println!("Hello, world!");
+fn main() -> std::io::Result<()> {
+    chess_engine::uci::run_uci(std::io::stdin().lock(), std::io::stdout())
 }
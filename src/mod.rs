@@ -1,3 +0,0 @@
-
-pub mod board;
-pub mod FEN;
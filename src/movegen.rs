@@ -0,0 +1,1466 @@
+use alloc::vec::Vec;
+use core::fmt;
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use crate::board::{BOARD_LENGTH, CastleSide, Chessman, Coordinate, MailboxBoard, Move, MoveKind, Piece, Team};
+
+/// the eight L-shaped jumps a knight can make, as (rank, file) offsets
+pub(crate) const KNIGHT_OFFSETS: [(i8, i8); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+
+/// the eight squares around a king, as (rank, file) offsets
+pub(crate) const KING_OFFSETS: [(i8, i8); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+/// directions a rook slides in, as (rank, file) steps
+pub const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// directions a bishop slides in, as (rank, file) steps
+pub const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// directions a queen slides in, both the rook and bishop ones
+pub const QUEEN_DIRECTIONS: [(i8, i8); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// The most legal moves any chess position has, so a buffer this big never needs to grow
+pub const MAX_MOVES: usize = 218;
+
+/// gets the unit step from one square toward another, if they share a rank, file or diagonal
+fn direction(from: Coordinate, to: Coordinate) -> Option<(i8, i8)> {
+    let d_rank = to.rank() as i8 - from.rank() as i8;
+    let d_file = to.file() as i8 - from.file() as i8;
+
+    if from == to || (d_rank != 0 && d_file != 0 && d_rank.abs() != d_file.abs()) {
+        return None;
+    }
+    Some((d_rank.signum(), d_file.signum()))
+}
+
+/// makes a quiet move or capture onto the target, or None if a friendly piece is in the way
+fn step_move(board: &MailboxBoard, from: Coordinate, target: Coordinate, team: Team) -> Option<Move> {
+    let capture = board.get_tile(target);
+
+    let kind = match capture.data() {
+        Some((occupant, _)) if occupant == team => return None,
+        Some(_) => MoveKind::Capture,
+        None => MoveKind::QuietMove,
+    };
+
+    Some(Move::new(kind, from, target, board.get_tile(from), capture))
+}
+
+/// Something the move generators can push moves onto, so the same code can fill different kinds of list
+pub trait MoveBuffer {
+    /// adds a move to the end
+    fn push(&mut self, m: Move);
+
+    /// checks whether the buffer wants any more moves, so the generators can stop early once it doesn't
+    fn is_full(&self) -> bool {
+        false
+    }
+}
+
+impl MoveBuffer for Vec<Move> {
+    fn push(&mut self, m: Move) {
+        Vec::push(self, m);
+    }
+}
+
+/// A list of up to [`MAX_MOVES`] moves kept in a fixed-size array, so filling it never touches the heap
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    /// Creates an empty list
+    pub fn new() -> Self {
+        let empty = Piece::new(None);
+        let filler = Move::new(MoveKind::QuietMove, Coordinate::new_unchecked(0), Coordinate::new_unchecked(0), empty, empty);
+        MoveList { moves: [filler; MAX_MOVES], len: 0 }
+    }
+
+    /// Adds a move to the end, panicking if the list already holds [`MAX_MOVES`] of them
+    pub fn push(&mut self, m: Move) {
+        self.moves[self.len] = m;
+        self.len += 1;
+    }
+
+    /// gets the number of moves in the list
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// checks whether the list has no moves in it
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// removes every move from the list
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// gets the moves in the list as a slice
+    pub fn as_slice(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+
+    /// iterates over the moves in the list, in the order they were pushed
+    pub fn iter(&self) -> core::slice::Iter<'_, Move> {
+        self.as_slice().iter()
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = core::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl MoveBuffer for MoveList {
+    fn push(&mut self, m: Move) {
+        MoveList::push(self, m);
+    }
+}
+
+/// passes moves on to another buffer, leaving out the ones that fail a test
+struct Filter<'a, B, F> {
+    buffer: &'a mut B,
+    keep: F,
+    /// how many moves have been put to the test, which stops once the buffer is full
+    tested: usize,
+}
+
+impl<'a, B: MoveBuffer, F: FnMut(Move) -> bool> Filter<'a, B, F> {
+    /// makes a filter that hasn't tested anything yet
+    fn new(buffer: &'a mut B, keep: F) -> Self {
+        Filter { buffer, keep, tested: 0 }
+    }
+}
+
+impl<B: MoveBuffer, F: FnMut(Move) -> bool> MoveBuffer for Filter<'_, B, F> {
+    fn push(&mut self, m: Move) {
+        if self.buffer.is_full() {
+            return;
+        }
+        self.tested += 1;
+        if (self.keep)(m) {
+            self.buffer.push(m);
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.buffer.is_full()
+    }
+}
+
+/// a buffer that's full as soon as any move is pushed, for finding out whether there are any
+struct Found(bool);
+
+impl MoveBuffer for Found {
+    fn push(&mut self, _: Move) {
+        self.0 = true;
+    }
+
+    fn is_full(&self) -> bool {
+        self.0
+    }
+}
+
+/// runs a generator that pushes onto a buffer, collecting what it pushes into a new Vec
+fn collect_moves(generate: impl FnOnce(&mut Vec<Move>)) -> Vec<Move> {
+    let mut moves = Vec::new();
+    generate(&mut moves);
+    moves
+}
+
+/// pushes the pseudo-legal moves for a knight, see [`knight_moves`]
+fn push_knight_moves(board: &MailboxBoard, from: Coordinate, team: Team, out: &mut impl MoveBuffer) {
+    for &(d_rank, d_file) in &KNIGHT_OFFSETS {
+        if let Some(m) = from.offset(d_rank, d_file).and_then(|target| step_move(board, from, target, team)) {
+            out.push(m);
+        }
+    }
+}
+
+/// Generates the pseudo-legal moves for a knight of the given team
+pub fn knight_moves(board: &MailboxBoard, from: Coordinate, team: Team) -> Vec<Move> {
+    collect_moves(|moves| push_knight_moves(board, from, team, moves))
+}
+
+/// pushes the pseudo-legal moves for a king, see [`king_moves`]
+fn push_king_moves(board: &MailboxBoard, from: Coordinate, team: Team, out: &mut impl MoveBuffer) {
+    for &(d_rank, d_file) in &KING_OFFSETS {
+        if let Some(m) = from.offset(d_rank, d_file).and_then(|target| step_move(board, from, target, team)) {
+            out.push(m);
+        }
+    }
+
+    let home_rank = match team {
+        Team::White => 0,
+        Team::Black => BOARD_LENGTH as u8 - 1,
+    };
+    let king = Piece::new(Some((team, Chessman::King)));
+    let rook = Piece::new(Some((team, Chessman::Rook)));
+
+    if from.rank() != home_rank || from.file() != 4 || board.get_tile(from) != king {
+        return;
+    }
+
+    // (side, kind, rook file, files that must be empty, king target file)
+    let castles = [
+        (CastleSide::KingSide, MoveKind::KingCastle, 7, &[5, 6][..], 6),
+        (CastleSide::QueenSide, MoveKind::QueenCastle, 0, &[1, 2, 3][..], 2),
+    ];
+
+    for (side, kind, rook_file, between, target_file) in castles {
+        let tile = |file: u8| board.get_tile(Coordinate::new_unchecked(home_rank * BOARD_LENGTH as u8 + file));
+
+        if board.can_castle(team, side)
+            && tile(rook_file) == rook
+            && between.iter().all(|&file| tile(file).data().is_none())
+        {
+            let target = Coordinate::new_unchecked(home_rank * BOARD_LENGTH as u8 + target_file);
+            out.push(Move::new(kind, from, target, king, Piece::new(None)));
+        }
+    }
+}
+
+/// Generates the pseudo-legal moves for a king of the given team
+///
+/// Castling is included when the team has the right and the squares between the king and rook are empty,
+/// but moving into, out of, or through check isn't considered.
+pub fn king_moves(board: &MailboxBoard, from: Coordinate, team: Team) -> Vec<Move> {
+    collect_moves(|moves| push_king_moves(board, from, team, moves))
+}
+
+/// pushes the pseudo-legal moves for a sliding piece, see [`sliding_moves`]
+fn push_sliding_moves(
+    board: &MailboxBoard,
+    from: Coordinate,
+    directions: &[(i8, i8)],
+    team: Team,
+    out: &mut impl MoveBuffer,
+) {
+    for &(d_rank, d_file) in directions {
+        let mut current = from;
+
+        while let Some(target) = current.offset(d_rank, d_file) {
+            match step_move(board, from, target, team) {
+                Some(m) => {
+                    let is_capture = m.kind() == MoveKind::Capture;
+                    out.push(m);
+                    if is_capture {
+                        break;
+                    }
+                }
+                None => break,
+            }
+            current = target;
+        }
+    }
+}
+
+/// Generates the pseudo-legal moves for a piece sliding along the given directions
+///
+/// Each ray stops at the edge of the board, before a friendly piece, or on an enemy piece as a capture.
+pub fn sliding_moves(board: &MailboxBoard, from: Coordinate, directions: &[(i8, i8)], team: Team) -> Vec<Move> {
+    collect_moves(|moves| push_sliding_moves(board, from, directions, team, moves))
+}
+
+/// the promotions a pawn can make by pushing onto the last rank
+pub(crate) const PROMOTIONS: [MoveKind; 4] = [
+    MoveKind::QueenPromotion,
+    MoveKind::RookPromotion,
+    MoveKind::BishopPromotion,
+    MoveKind::KnightPromotion,
+];
+
+/// the promotions a pawn can make by capturing onto the last rank
+pub(crate) const PROMOTION_CAPTURES: [MoveKind; 4] = [
+    MoveKind::QueenPromotionCapture,
+    MoveKind::RookPromotionCapture,
+    MoveKind::BishopPromotionCapture,
+    MoveKind::KnightPromotionCapture,
+];
+
+/// pushes the pseudo-legal moves for a pawn, see [`pawn_moves`]
+fn push_pawn_moves(board: &MailboxBoard, from: Coordinate, team: Team, out: &mut impl MoveBuffer) {
+    let (forward, start_rank, last_rank) = match team {
+        Team::White => (1, 1, BOARD_LENGTH as u8 - 1),
+        Team::Black => (-1, BOARD_LENGTH as u8 - 2, 0),
+    };
+
+    let piece = board.get_tile(from);
+
+    let mut push = |kind: MoveKind, target: Coordinate, capture: Piece| {
+        out.push(Move::new(kind, from, target, piece, capture));
+    };
+
+    if let Some(target) = from.offset(forward, 0).filter(|&t| board.get_tile(t).data().is_none()) {
+        if target.rank() == last_rank {
+            for kind in PROMOTIONS {
+                push(kind, target, Piece::new(None));
+            }
+        } else {
+            push(MoveKind::QuietMove, target, Piece::new(None));
+
+            if from.rank() == start_rank {
+                if let Some(double) = target.offset(forward, 0).filter(|&t| board.get_tile(t).data().is_none()) {
+                    push(MoveKind::DoublePawnPush, double, Piece::new(None));
+                }
+            }
+        }
+    }
+
+    for d_file in [-1, 1] {
+        let Some(target) = from.offset(forward, d_file) else { continue };
+
+        let capture = board.get_tile(target);
+
+        if board.en_passant() == Some(target) {
+            // the pawn being taken sits beside this one, on the target's file
+            let passed = Coordinate::new_unchecked(from.rank() * BOARD_LENGTH as u8 + target.file());
+            let enemy_pawn = Piece::new(Some((team.opponent(), Chessman::Pawn)));
+
+            if capture.data().is_none() && board.get_tile(passed) == enemy_pawn {
+                push(MoveKind::EPCapture, target, enemy_pawn);
+            }
+            continue;
+        }
+
+        match capture.data() {
+            Some((occupant, _)) if occupant != team => {
+                if target.rank() == last_rank {
+                    for kind in PROMOTION_CAPTURES {
+                        push(kind, target, capture);
+                    }
+                } else {
+                    push(MoveKind::Capture, target, capture);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Generates the pseudo-legal moves for a pawn of the given team
+///
+/// White pawns move toward increasing rank and Black pawns toward decreasing rank.
+pub fn pawn_moves(board: &MailboxBoard, from: Coordinate, team: Team) -> Vec<Move> {
+    collect_moves(|moves| push_pawn_moves(board, from, team, moves))
+}
+
+/// pushes the pseudo-legal moves for every piece of a team, see [`team_moves`]
+fn push_team_moves(board: &MailboxBoard, team: Team, out: &mut impl MoveBuffer) {
+    for from in Coordinate::all() {
+        if out.is_full() {
+            return;
+        }
+        match board.get_tile(from).data() {
+            Some((owner, chessman)) if owner == team => match chessman {
+                Chessman::King => push_king_moves(board, from, team, out),
+                Chessman::Queen => push_sliding_moves(board, from, &QUEEN_DIRECTIONS, team, out),
+                Chessman::Bishop => push_sliding_moves(board, from, &BISHOP_DIRECTIONS, team, out),
+                Chessman::Knight => push_knight_moves(board, from, team, out),
+                Chessman::Rook => push_sliding_moves(board, from, &ROOK_DIRECTIONS, team, out),
+                Chessman::Pawn => push_pawn_moves(board, from, team, out),
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Generates the pseudo-legal moves for every piece of the given team
+pub fn team_moves(board: &MailboxBoard, team: Team) -> Vec<Move> {
+    collect_moves(|moves| push_team_moves(board, team, moves))
+}
+
+/// Counts the pseudo-legal moves for every piece of the given team, without building a list of them
+pub fn count_team_moves(board: &MailboxBoard, team: Team) -> usize {
+    /// a buffer that only keeps count of what's pushed onto it
+    struct Count(usize);
+
+    impl MoveBuffer for Count {
+        fn push(&mut self, _: Move) {
+            self.0 += 1;
+        }
+    }
+
+    let mut count = Count(0);
+    push_team_moves(board, team, &mut count);
+    count.0
+}
+
+/// Whether a game can carry on, judged from the side to move's legal moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate { winner: Team },
+    Stalemate,
+}
+
+impl MailboxBoard {
+    /// Checks whether any piece of the given team attacks a square
+    pub fn is_attacked(&self, square: Coordinate, by: Team) -> bool {
+        self.is_attacked_after(square, by, 0, 0)
+    }
+
+    /// checks whether a team attacks a square once the vacated squares are emptied and the filled ones blocked
+    ///
+    /// Both are bitboards. This tells whether a move would leave the king attacked without making the move on a
+    /// copy of the board, since the pieces that move away or get taken are on vacated squares.
+    fn is_attacked_after(&self, square: Coordinate, by: Team, vacated: u64, filled: u64) -> bool {
+        let piece_at = |target: Coordinate| {
+            if vacated & (1 << target.index()) != 0 {
+                None
+            } else {
+                self.get_tile(target).data()
+            }
+        };
+        let holds = |target: Option<Coordinate>, chessmen: &[Chessman]| {
+            target
+                .filter(|t| filled & (1 << t.index()) == 0)
+                .and_then(piece_at)
+                .is_some_and(|(team, chessman)| team == by && chessmen.contains(&chessman))
+        };
+
+        // pawns attack diagonally forward, so look diagonally backward from the square
+        let pawn_rank = match by {
+            Team::White => -1,
+            Team::Black => 1,
+        };
+        if [-1, 1].iter().any(|&d_file| holds(square.offset(pawn_rank, d_file), &[Chessman::Pawn])) {
+            return true;
+        }
+
+        if KNIGHT_OFFSETS.iter().any(|&(d_rank, d_file)| holds(square.offset(d_rank, d_file), &[Chessman::Knight])) {
+            return true;
+        }
+
+        if KING_OFFSETS.iter().any(|&(d_rank, d_file)| holds(square.offset(d_rank, d_file), &[Chessman::King])) {
+            return true;
+        }
+
+        let rays = [
+            (&ROOK_DIRECTIONS, [Chessman::Rook, Chessman::Queen]),
+            (&BISHOP_DIRECTIONS, [Chessman::Bishop, Chessman::Queen]),
+        ];
+        for (directions, sliders) in rays {
+            for &(d_rank, d_file) in directions {
+                let mut current = square;
+
+                while let Some(next) = current.offset(d_rank, d_file) {
+                    if filled & (1 << next.index()) != 0 || piece_at(next).is_some() {
+                        if holds(Some(next), &sliders) {
+                            return true;
+                        }
+                        break;
+                    }
+                    current = next;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Checks whether a team's king is attacked, a team without a king is never in check
+    pub fn in_check(&self, team: Team) -> bool {
+        self.king_square(team).is_some_and(|king| self.is_attacked(king, team.opponent()))
+    }
+
+    /// Checks whether making a move would put the other team's king in check, without making it
+    ///
+    /// This finds direct checks by the moved piece, or the piece it promotes to, and discovered checks by a piece
+    /// it was blocking. Castling and en passant move or remove a second piece, so for those the move is tried
+    /// on a copy of the board instead.
+    pub fn gives_check(&self, m: Move) -> bool {
+        let Some((team, chessman)) = m.piece().data() else { return false };
+        let Some(king) = self.king_square(team.opponent()) else { return false };
+
+        if matches!(m.kind(), MoveKind::KingCastle | MoveKind::QueenCastle | MoveKind::EPCapture) {
+            let mut after = self.clone();
+            after.make_move(m);
+            return after.in_check(team.opponent());
+        }
+
+        let placed = m.kind().promotion_piece().unwrap_or(chessman);
+
+        let forward = match team {
+            Team::White => 1,
+            Team::Black => -1,
+        };
+        let reaches_king = |(d_rank, d_file): (i8, i8)| m.target().offset(d_rank, d_file) == Some(king);
+        let direct = match placed {
+            Chessman::Pawn => [(forward, -1), (forward, 1)].into_iter().any(reaches_king),
+            Chessman::Knight => KNIGHT_OFFSETS.into_iter().any(reaches_king),
+            _ => false,
+        };
+        if direct {
+            return true;
+        }
+
+        // with the move made, look along every line out of the king for the first piece in the way, which
+        // covers the moved piece sliding onto a line with the king and uncovering another piece's line
+        for &(d_rank, d_file) in &QUEEN_DIRECTIONS {
+            let sliders = if d_rank == 0 || d_file == 0 {
+                [Chessman::Rook, Chessman::Queen]
+            } else {
+                [Chessman::Bishop, Chessman::Queen]
+            };
+
+            let mut current = king;
+            while let Some(next) = current.offset(d_rank, d_file) {
+                let piece = if next == m.target() {
+                    Some((team, placed))
+                } else if next == m.origin() {
+                    None
+                } else {
+                    self.get_tile(next).data()
+                };
+
+                if let Some((owner, occupant)) = piece {
+                    if owner == team && sliders.contains(&occupant) {
+                        return true;
+                    }
+                    break;
+                }
+                current = next;
+            }
+        }
+
+        false
+    }
+
+    /// Finds the pieces of a team that are pinned to its king, as a bitboard with a bit set for each square
+    ///
+    /// A piece is pinned when it's the only thing between its king and an enemy rook, bishop or queen that
+    /// could otherwise slide onto the king, so moving it off that line would leave the king in check.
+    pub fn pinned_pieces(&self, team: Team) -> u64 {
+        let Some(king) = self.king_square(team) else { return 0 };
+        let mut pinned = 0;
+
+        for (directions, sliders) in [
+            (&ROOK_DIRECTIONS, [Chessman::Rook, Chessman::Queen]),
+            (&BISHOP_DIRECTIONS, [Chessman::Bishop, Chessman::Queen]),
+        ] {
+            for &(d_rank, d_file) in directions {
+                let mut shield = None;
+                let mut current = king;
+
+                while let Some(next) = current.offset(d_rank, d_file) {
+                    match (self.get_tile(next).data(), shield) {
+                        (None, _) => {}
+                        (Some((owner, _)), None) if owner == team => shield = Some(next),
+                        (Some((owner, chessman)), Some(shield)) if owner != team && sliders.contains(&chessman) => {
+                            pinned |= 1 << shield.index();
+                            break;
+                        }
+                        _ => break,
+                    }
+                    current = next;
+                }
+            }
+        }
+
+        pinned
+    }
+
+    /// Finds every piece of the given team that attacks a square, whether or not the square is occupied
+    ///
+    /// Only direct attacks count, so a rook stacked behind another on the same file isn't included until the one
+    /// in front moves away.
+    pub fn attackers(&self, square: Coordinate, by: Team) -> Vec<Coordinate> {
+        let mut attackers = Vec::new();
+        self.for_each_attacker(square, by, |attacker| attackers.push(attacker));
+        attackers
+    }
+
+    /// calls `found` with the square of each attacker [`attackers`](Self::attackers) would give, in the same order
+    fn for_each_attacker(&self, square: Coordinate, by: Team, mut found: impl FnMut(Coordinate)) {
+        let holds = |target: &Coordinate, chessmen: &[Chessman]| {
+            self.get_tile(*target).data().is_some_and(|(team, chessman)| team == by && chessmen.contains(&chessman))
+        };
+        let steps = |offsets: &'static [(i8, i8)], chessman: Chessman| {
+            offsets
+                .iter()
+                .filter_map(move |&(d_rank, d_file)| square.offset(d_rank, d_file))
+                .filter(move |target| holds(target, &[chessman]))
+        };
+
+        // pawns attack diagonally forward, so look diagonally backward from the square
+        let pawn_offsets: &[(i8, i8)] = match by {
+            Team::White => &[(-1, -1), (-1, 1)],
+            Team::Black => &[(1, -1), (1, 1)],
+        };
+        steps(pawn_offsets, Chessman::Pawn)
+            .chain(steps(&KNIGHT_OFFSETS, Chessman::Knight))
+            .chain(steps(&KING_OFFSETS, Chessman::King))
+            .for_each(&mut found);
+
+        for (directions, sliders) in [
+            (&ROOK_DIRECTIONS, [Chessman::Rook, Chessman::Queen]),
+            (&BISHOP_DIRECTIONS, [Chessman::Bishop, Chessman::Queen]),
+        ] {
+            for &(d_rank, d_file) in directions {
+                let mut current = square;
+
+                while let Some(next) = current.offset(d_rank, d_file) {
+                    if self.get_tile(next).data().is_some() {
+                        if holds(&next, &sliders) {
+                            found(next);
+                        }
+                        break;
+                    }
+                    current = next;
+                }
+            }
+        }
+    }
+
+    /// Generates the legal moves for the side to move
+    ///
+    /// Out of check, a pinned piece's move is legal when it stays on the line through its king and any other
+    /// piece's move is always legal, so only king moves and en passant need a closer look. In check, the moves
+    /// come from [`evasion_moves`](Self::evasion_moves).
+    pub fn legal_moves(&self) -> Vec<Move> {
+        collect_moves(|moves| self.generate_moves_into(moves))
+    }
+
+    /// Adds the legal moves for the side to move onto the end of a buffer
+    ///
+    /// These are the same moves as [`legal_moves`](Self::legal_moves), but reusing one buffer saves allocating a
+    /// new list every time, and a [`MoveList`] doesn't allocate at all. The buffer isn't cleared first, so that's
+    /// up to the caller.
+    pub fn generate_moves_into(&self, buf: &mut impl MoveBuffer) {
+        self.push_legal_moves(buf);
+    }
+
+    /// Generates the legal moves for the side to move into a [`MoveList`], without any heap allocation
+    pub fn legal_move_list(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        self.push_legal_moves(&mut moves);
+        moves
+    }
+
+    /// pushes the legal moves for the side to move, see [`legal_moves`](Self::legal_moves), and gets how many
+    /// pseudo-legal moves had their legality checked along the way
+    fn push_legal_moves(&self, out: &mut impl MoveBuffer) -> usize {
+        let mover = self.side_to_move();
+        let Some(king) = self.king_square(mover) else {
+            push_team_moves(self, mover, out);
+            return 0;
+        };
+        let enemy = mover.opponent();
+        if self.is_attacked(king, enemy) {
+            return self.push_evasion_moves(king, out);
+        }
+
+        let pinned = self.pinned_pieces(mover);
+        // with the king taken off, squares behind it along an attacker's ray show up as attacked
+        let kingless = 1 << king.index();
+
+        let mut legal = Filter::new(out, |m: Move| match m.kind() {
+            MoveKind::KingCastle | MoveKind::QueenCastle => {
+                let passed = Coordinate::new_unchecked((m.origin().index() + m.target().index()) / 2);
+                !self.is_attacked_after(passed, enemy, kingless, 0)
+                    && !self.is_attacked_after(m.target(), enemy, kingless, 0)
+            }
+            _ if m.origin() == king => !self.is_attacked_after(m.target(), enemy, kingless, 0),
+            // taking en passant clears two squares on one rank, which can uncover an attack no pin covers
+            MoveKind::EPCapture => self.en_passant_is_safe(m, king),
+            _ if pinned & (1 << m.origin().index()) != 0 => direction(king, m.target()) == direction(king, m.origin()),
+            _ => true,
+        });
+        push_team_moves(self, mover, &mut legal);
+        legal.tested
+    }
+
+    /// checks that taking en passant doesn't leave the mover's king, on the given square, attacked
+    fn en_passant_is_safe(&self, m: Move, king: Coordinate) -> bool {
+        let vacated = 1 << m.origin().index() | 1 << MailboxBoard::en_passant_victim(&m).index();
+        !self.is_attacked_after(king, self.side_to_move().opponent(), vacated, 1 << m.target().index())
+    }
+
+    /// Generates the legal moves for the side to move when its king is in check
+    ///
+    /// Against a single checker the only ways out are moving the king, taking the checker, or putting a piece in
+    /// the way of a sliding checker, and against a double check only the king can move. Out of check this gives
+    /// the same moves as [`legal_moves`](Self::legal_moves).
+    pub fn evasion_moves(&self) -> Vec<Move> {
+        let mover = self.side_to_move();
+        match self.king_square(mover) {
+            Some(king) if self.is_attacked(king, mover.opponent()) => {
+                collect_moves(|moves| {
+                    self.push_evasion_moves(king, moves);
+                })
+            }
+            _ => self.legal_moves(),
+        }
+    }
+
+    /// pushes the ways out of check for the side to move, whose king is on the given square, and gets how many
+    /// pseudo-legal moves were checked
+    fn push_evasion_moves(&self, king: Coordinate, out: &mut impl MoveBuffer) -> usize {
+        let mover = self.side_to_move();
+        let enemy = mover.opponent();
+
+        let mut checkers = 0;
+        let mut checker = king;
+        self.for_each_attacker(king, enemy, |attacker| {
+            checkers += 1;
+            checker = attacker;
+        });
+
+        let kingless = 1 << king.index();
+        let mut king_moves = Filter::new(&mut *out, |m: Move| {
+            !matches!(m.kind(), MoveKind::KingCastle | MoveKind::QueenCastle)
+                && !self.is_attacked_after(m.target(), enemy, kingless, 0)
+        });
+        push_king_moves(self, king, mover, &mut king_moves);
+        let tested = king_moves.tested;
+
+        if checkers != 1 || out.is_full() {
+            return tested;
+        }
+
+        // the checker's square and, for a slider, every square between it and the king
+        let mut covers: u64 = 1 << checker.index();
+        if let Some((d_rank, d_file)) = direction(king, checker) {
+            let mut current = king;
+            while let Some(next) = current.offset(d_rank, d_file).filter(|&next| next != checker) {
+                covers |= 1 << next.index();
+                current = next;
+            }
+        }
+
+        // a pinned piece can never get in the way of another line or take a piece that isn't on its own
+        let pinned = self.pinned_pieces(mover);
+        let mut others = Filter::new(out, |m: Move| {
+            if m.origin() == king || pinned & (1 << m.origin().index()) != 0 {
+                return false;
+            }
+            if m.kind() == MoveKind::EPCapture {
+                return self.en_passant_is_safe(m, king);
+            }
+            covers & (1 << m.target().index()) != 0
+        });
+        push_team_moves(self, mover, &mut others);
+        tested + others.tested
+    }
+
+    /// Checks whether the side to move has any legal move, stopping at the first one found
+    ///
+    /// This is quicker than generating every legal move just to see whether there are none.
+    pub fn has_legal_move(&self) -> bool {
+        self.find_legal_move().0
+    }
+
+    /// checks whether the side to move has a legal move, along with how many moves were tested to find out
+    fn find_legal_move(&self) -> (bool, usize) {
+        let mut found = Found(false);
+        let tested = self.push_legal_moves(&mut found);
+        (found.0, tested)
+    }
+
+    /// Counts the leaf nodes of the legal move tree to the given depth
+    ///
+    /// This is the standard way to check move generation against known results. Each node's moves go in a
+    /// [`MoveList`], so the count doesn't allocate as it goes.
+    pub fn perft(&mut self, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_move_list();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for &m in &moves {
+            let undo = self.make_move(m);
+            nodes += self.perft(depth - 1);
+            self.unmake_move(undo);
+        }
+
+        nodes
+    }
+
+    /// Breaks perft down by root move, sorted by the moves' UCI strings
+    ///
+    /// Comparing this against a reference engine narrows a perft mismatch down to the moves responsible.
+    pub fn perft_divide(&mut self, depth: u8) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+
+        let mut divide: Vec<(Move, u64)> = self
+            .legal_moves()
+            .into_iter()
+            .map(|m| {
+                let undo = self.make_move(m);
+                let nodes = self.perft(depth - 1);
+                self.unmake_move(undo);
+                (m, nodes)
+            })
+            .collect();
+
+        // the same order as sorting the UCI strings, without having to write them
+        divide.sort_by_key(|(m, _)| {
+            (m.origin().file(), m.origin().rank(), m.target().file(), m.target().rank(), m.promotion_letter())
+        });
+        divide
+    }
+
+    /// Picks one of the legal moves uniformly at random, or None if there aren't any
+    ///
+    /// The random number generator is passed in, so seeding it gives the same moves every time.
+    pub fn random_move(&self, rng: &mut impl RngCore) -> Option<Move> {
+        self.legal_moves().choose(rng).copied()
+    }
+
+    /// Works out whether the side to move has been checkmated or stalemated
+    pub fn status(&self) -> GameStatus {
+        let mover = self.side_to_move();
+
+        if self.has_legal_move() {
+            GameStatus::Ongoing
+        } else if self.in_check(mover) {
+            GameStatus::Checkmate { winner: mover.opponent() }
+        } else {
+            GameStatus::Stalemate
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    fn square(name: &str) -> Coordinate {
+        Coordinate::from_algebraic(name).expect("bad test square")
+    }
+
+    /// gets the targets of the moves as sorted algebraic strings
+    fn targets(moves: &[Move]) -> Vec<String> {
+        let mut targets: Vec<String> = moves.iter().map(|m| m.target().to_string()).collect();
+        targets.sort();
+        targets
+    }
+
+    #[test]
+    fn test_knight_moves() {
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("b1"), Piece::new(Some((Team::White, Chessman::Knight))));
+
+        let moves = knight_moves(&board, square("b1"), Team::White);
+        assert_eq!(targets(&moves), ["a3", "c3", "d2"]);
+        assert!(moves.iter().all(|m| m.kind() == MoveKind::QuietMove && m.origin() == square("b1")));
+
+        for corner in ["a1", "h1", "a8", "h8"] {
+            assert_eq!(knight_moves(&board, square(corner), Team::White).len(), 2, "{}", corner);
+        }
+
+        // knights on the h file shouldn't wrap around onto the a file
+        assert_eq!(targets(&knight_moves(&board, square("h4"), Team::White)), ["f3", "f5", "g2", "g6"]);
+    }
+
+    #[test]
+    fn test_knight_captures() {
+        let board = MailboxBoard::starting_position();
+
+        // own pawn on d2 blocks that jump
+        assert_eq!(targets(&knight_moves(&board, square("b1"), Team::White)), ["a3", "c3"]);
+
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("e4"), Piece::new(Some((Team::White, Chessman::Knight))));
+        board.set_tile(square("f6"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+        board.set_tile(square("d6"), Piece::new(Some((Team::White, Chessman::Pawn))));
+
+        let moves = knight_moves(&board, square("e4"), Team::White);
+        assert_eq!(moves.len(), 7);
+
+        let capture = moves.iter().find(|m| m.target() == square("f6")).expect("missing capture");
+        assert_eq!(capture.kind(), MoveKind::Capture);
+        assert_eq!(capture.capture().data(), Some((Team::Black, Chessman::Pawn)));
+        assert_eq!(capture.piece().data(), Some((Team::White, Chessman::Knight)));
+    }
+
+    #[test]
+    fn test_sliding_moves_empty_board() {
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("d4"), Piece::new(Some((Team::White, Chessman::Rook))));
+
+        let moves = sliding_moves(&board, square("d4"), &ROOK_DIRECTIONS, Team::White);
+        assert_eq!(
+            targets(&moves),
+            ["a4", "b4", "c4", "d1", "d2", "d3", "d5", "d6", "d7", "d8", "e4", "f4", "g4", "h4"]
+        );
+        assert!(moves.iter().all(|m| m.kind() == MoveKind::QuietMove));
+
+        assert_eq!(sliding_moves(&board, square("d4"), &BISHOP_DIRECTIONS, Team::White).len(), 13);
+        assert_eq!(sliding_moves(&board, square("d4"), &QUEEN_DIRECTIONS, Team::White).len(), 27);
+        assert_eq!(sliding_moves(&board, square("h1"), &QUEEN_DIRECTIONS, Team::White).len(), 21);
+    }
+
+    #[test]
+    fn test_sliding_moves_blocked() {
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("d4"), Piece::new(Some((Team::White, Chessman::Rook))));
+        board.set_tile(square("d6"), Piece::new(Some((Team::Black, Chessman::Knight))));
+        board.set_tile(square("f4"), Piece::new(Some((Team::White, Chessman::Pawn))));
+
+        let moves = sliding_moves(&board, square("d4"), &ROOK_DIRECTIONS, Team::White);
+        assert_eq!(targets(&moves), ["a4", "b4", "c4", "d1", "d2", "d3", "d5", "d6", "e4"]);
+
+        let captures: Vec<&Move> = moves.iter().filter(|m| m.kind() == MoveKind::Capture).collect();
+        assert_eq!(captures.len(), 1);
+        assert_eq!(captures[0].target(), square("d6"));
+
+        // bishops in the starting position are boxed in by their own pawns
+        let board = MailboxBoard::starting_position();
+        assert!(sliding_moves(&board, square("c1"), &BISHOP_DIRECTIONS, Team::White).is_empty());
+        assert!(sliding_moves(&board, square("d8"), &QUEEN_DIRECTIONS, Team::Black).is_empty());
+    }
+
+    #[test]
+    fn test_pawn_pushes() {
+        let board = MailboxBoard::starting_position();
+
+        let moves = pawn_moves(&board, square("e2"), Team::White);
+        assert_eq!(targets(&moves), ["e3", "e4"]);
+        assert!(moves.iter().any(|m| m.target() == square("e4") && m.kind() == MoveKind::DoublePawnPush));
+        assert!(moves.iter().any(|m| m.target() == square("e3") && m.kind() == MoveKind::QuietMove));
+
+        let moves = pawn_moves(&board, square("d7"), Team::Black);
+        assert_eq!(targets(&moves), ["d5", "d6"]);
+
+        // no double push once the pawn has moved, and none through a blocker
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("c3"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("g2"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("g3"), Piece::new(Some((Team::Black, Chessman::Knight))));
+        assert_eq!(targets(&pawn_moves(&board, square("c3"), Team::White)), ["c4"]);
+        assert!(pawn_moves(&board, square("g2"), Team::White).is_empty());
+    }
+
+    #[test]
+    fn test_pawn_captures() {
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("e4"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("d5"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+        board.set_tile(square("f5"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("e5"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+
+        let moves = pawn_moves(&board, square("e4"), Team::White);
+        assert_eq!(targets(&moves), ["d5"]);
+        assert_eq!(moves[0].kind(), MoveKind::Capture);
+
+        // a pawn on the a file can't capture off the left edge of the board
+        board.set_tile(square("a2"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("h3"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+        assert_eq!(targets(&pawn_moves(&board, square("a2"), Team::White)), ["a3", "a4"]);
+    }
+
+    #[test]
+    fn test_pawn_promotions() {
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("a7"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("b8"), Piece::new(Some((Team::Black, Chessman::Rook))));
+
+        let moves = pawn_moves(&board, square("a7"), Team::White);
+        assert_eq!(moves.len(), 8);
+
+        let pushes: Vec<&Move> = moves.iter().filter(|m| m.target() == square("a8")).collect();
+        assert_eq!(pushes.len(), 4);
+        assert!(pushes.iter().any(|m| m.kind() == MoveKind::QueenPromotion));
+        assert!(pushes.iter().any(|m| m.kind() == MoveKind::RookPromotion));
+        assert!(pushes.iter().any(|m| m.kind() == MoveKind::BishopPromotion));
+        assert!(pushes.iter().any(|m| m.kind() == MoveKind::KnightPromotion));
+
+        let captures: Vec<&Move> = moves.iter().filter(|m| m.target() == square("b8")).collect();
+        assert_eq!(captures.len(), 4);
+        assert!(captures.iter().any(|m| m.kind() == MoveKind::QueenPromotionCapture));
+        assert!(captures.iter().any(|m| m.kind() == MoveKind::RookPromotionCapture));
+        assert!(captures.iter().any(|m| m.kind() == MoveKind::BishopPromotionCapture));
+        assert!(captures.iter().any(|m| m.kind() == MoveKind::KnightPromotionCapture));
+        assert!(captures.iter().all(|m| m.capture().data() == Some((Team::Black, Chessman::Rook))));
+
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("h2"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+        let moves = pawn_moves(&board, square("h2"), Team::Black);
+        assert_eq!(moves.len(), 4);
+        assert!(moves.iter().all(|m| m.target() == square("h1")));
+    }
+
+    #[test]
+    fn test_king_moves() {
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("h1"), Piece::new(Some((Team::White, Chessman::King))));
+        board.set_tile(square("e4"), Piece::new(Some((Team::Black, Chessman::King))));
+
+        assert_eq!(targets(&king_moves(&board, square("h1"), Team::White)), ["g1", "g2", "h2"]);
+        assert_eq!(
+            targets(&king_moves(&board, square("e4"), Team::Black)),
+            ["d3", "d4", "d5", "e3", "e5", "f3", "f4", "f5"]
+        );
+
+        board.set_tile(square("d5"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+        board.set_tile(square("f3"), Piece::new(Some((Team::White, Chessman::Pawn))));
+
+        let moves = king_moves(&board, square("e4"), Team::Black);
+        assert_eq!(moves.len(), 7);
+        assert!(moves.iter().all(|m| m.target() != square("d5")));
+        assert_eq!(moves.iter().find(|m| m.target() == square("f3")).unwrap().kind(), MoveKind::Capture);
+    }
+
+    #[test]
+    fn test_team_moves() {
+        let board = MailboxBoard::starting_position();
+
+        let moves = board.get_moves();
+        assert_eq!(moves.len(), 20);
+        assert_eq!(moves.iter().filter(|m| m.piece().data() == Some((Team::White, Chessman::Pawn))).count(), 16);
+        assert_eq!(moves.iter().filter(|m| m.piece().data() == Some((Team::White, Chessman::Knight))).count(), 4);
+
+        assert_eq!(team_moves(&board, Team::Black).len(), 20);
+    }
+
+    #[test]
+    fn test_count_team_moves() {
+        let board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        for team in Team::all() {
+            assert_eq!(count_team_moves(&board, team), team_moves(&board, team).len());
+        }
+        assert_eq!(count_team_moves(&MailboxBoard::starting_position(), Team::Black), 20);
+    }
+
+    #[test]
+    fn test_castling_moves() {
+        let board = MailboxBoard::starting_position();
+        assert!(king_moves(&board, square("e1"), Team::White).is_empty());
+
+        let mut board = MailboxBoard::starting_position();
+        board.clear_tile(square("f1"));
+        board.clear_tile(square("g1"));
+
+        let moves = king_moves(&board, square("e1"), Team::White);
+        let castle = moves.iter().find(|m| m.kind() == MoveKind::KingCastle).expect("missing castle");
+        assert_eq!(castle.target(), square("g1"));
+        assert_eq!(targets(&moves), ["f1", "g1"]);
+
+        board.revoke_castle(Team::White, CastleSide::KingSide);
+        assert!(king_moves(&board, square("e1"), Team::White).iter().all(|m| m.kind() != MoveKind::KingCastle));
+
+        let board = MailboxBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Qk - 0 1").unwrap();
+        let black: Vec<MoveKind> = king_moves(&board, square("e8"), Team::Black).iter().map(|m| m.kind()).collect();
+        assert!(black.contains(&MoveKind::KingCastle));
+        assert!(!black.contains(&MoveKind::QueenCastle));
+        let white: Vec<MoveKind> = king_moves(&board, square("e1"), Team::White).iter().map(|m| m.kind()).collect();
+        assert!(!white.contains(&MoveKind::KingCastle));
+        assert!(white.contains(&MoveKind::QueenCastle));
+
+        // the b file has to be empty for queenside castling even though the king doesn't cross it
+        let board = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/RN2K3 w Q - 0 1").unwrap();
+        assert!(king_moves(&board, square("e1"), Team::White).iter().all(|m| m.kind() != MoveKind::QueenCastle));
+    }
+
+    #[test]
+    fn test_en_passant_moves() {
+        // 1. e4 a6 2. e5 d5, White can take on d6
+        let board = MailboxBoard::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3").unwrap();
+
+        let moves = pawn_moves(&board, square("e5"), Team::White);
+        assert_eq!(targets(&moves), ["d6", "e6"]);
+
+        let ep = moves.iter().find(|m| m.target() == square("d6")).unwrap();
+        assert_eq!(ep.kind(), MoveKind::EPCapture);
+        assert_eq!(ep.capture(), Piece::new(Some((Team::Black, Chessman::Pawn))));
+
+        // pawns that aren't beside the pushed pawn can't take it
+        let board = MailboxBoard::from_fen("rnbqkbnr/1pp1pppp/p7/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3").unwrap();
+        assert!(pawn_moves(&board, square("e4"), Team::White).iter().all(|m| m.kind() != MoveKind::EPCapture));
+
+        // same thing for Black capturing toward rank 3
+        let board = MailboxBoard::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3").unwrap();
+        let moves = pawn_moves(&board, square("d4"), Team::Black);
+        assert_eq!(targets(&moves), ["d3", "e3"]);
+        assert!(moves.iter().any(|m| m.kind() == MoveKind::EPCapture && m.target() == square("e3")));
+    }
+
+    #[test]
+    fn test_is_attacked_starting_position() {
+        let board = MailboxBoard::starting_position();
+
+        assert!(board.is_attacked(square("d3"), Team::White));
+        assert!(board.is_attacked(square("c3"), Team::White));
+        assert!(board.is_attacked(square("f1"), Team::White));
+        assert!(!board.is_attacked(square("e4"), Team::Black));
+        assert!(!board.is_attacked(square("e4"), Team::White));
+        assert!(board.is_attacked(square("f6"), Team::Black));
+        assert!(!board.is_attacked(square("d4"), Team::White));
+    }
+
+    #[test]
+    fn test_is_attacked_pieces() {
+        let mut board = MailboxBoard::empty();
+        board.set_tile(square("d4"), Piece::new(Some((Team::Black, Chessman::Rook))));
+        board.set_tile(square("d6"), Piece::new(Some((Team::White, Chessman::Pawn))));
+        board.set_tile(square("b2"), Piece::new(Some((Team::Black, Chessman::Bishop))));
+        board.set_tile(square("h8"), Piece::new(Some((Team::Black, Chessman::Queen))));
+        board.set_tile(square("e2"), Piece::new(Some((Team::Black, Chessman::Pawn))));
+
+        // rook rays stop at the first piece
+        assert!(board.is_attacked(square("d6"), Team::Black));
+        assert!(!board.is_attacked(square("d7"), Team::Black));
+        assert!(board.is_attacked(square("a4"), Team::Black));
+
+        // the queen's long diagonal reaches e5 before running into the rook on d4
+        assert!(board.is_attacked(square("e5"), Team::Black));
+        assert!(board.is_attacked(square("a1"), Team::Black));
+        assert!(board.is_attacked(square("a8"), Team::Black));
+
+        // Black pawns attack toward rank 1
+        assert!(board.is_attacked(square("d1"), Team::Black));
+        assert!(board.is_attacked(square("f1"), Team::Black));
+        assert!(!board.is_attacked(square("e1"), Team::Black));
+        assert!(board.is_attacked(square("d3"), Team::Black));
+
+        // White pawns attack toward rank 8
+        assert!(board.is_attacked(square("c7"), Team::White));
+        assert!(!board.is_attacked(square("c5"), Team::White));
+    }
+
+    #[test]
+    fn test_attackers() {
+        let board = MailboxBoard::from_fen("3rk3/5b2/1n6/1Q1p4/2P2N2/3R4/3R4/4K3 w - - 0 1").unwrap();
+        let attackers = |team: Team| {
+            let mut squares: Vec<String> = board.attackers(square("d5"), team).iter().map(|s| s.to_string()).collect();
+            squares.sort();
+            squares
+        };
+
+        // the rook on d2 is stacked behind the one on d3, so it doesn't count yet
+        assert_eq!(attackers(Team::White), ["b5", "c4", "d3", "f4"]);
+        // defending the pawn on d5 counts as attacking its square
+        assert_eq!(attackers(Team::Black), ["b6", "d8", "f7"]);
+        assert!(board.attackers(square("a8"), Team::White).is_empty());
+    }
+
+    #[test]
+    fn test_in_check() {
+        let board = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(board.king_square(Team::White), Some(square("e1")));
+        assert_eq!(board.king_square(Team::Black), Some(square("e8")));
+        assert!(!board.in_check(Team::White));
+        assert!(!board.in_check(Team::Black));
+
+        let board = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/4RK2 b - - 0 1").unwrap();
+        assert!(board.in_check(Team::Black));
+        assert!(!board.in_check(Team::White));
+
+        // a piece in between blocks the check
+        let board = MailboxBoard::from_fen("4k3/4n3/8/8/8/8/8/4RK2 b - - 0 1").unwrap();
+        assert!(!board.in_check(Team::Black));
+
+        let board = MailboxBoard::empty();
+        assert_eq!(board.king_square(Team::White), None);
+        assert!(!board.in_check(Team::White));
+    }
+
+    #[test]
+    fn test_legal_moves_pin() {
+        // the knight on e2 is pinned to its king by the rook on e7
+        let board = MailboxBoard::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+
+        let moves = board.legal_moves();
+        assert!(moves.iter().all(|m| m.origin() != square("e2")));
+        assert_eq!(targets(&moves), ["d1", "d2", "f1", "f2"]);
+        assert_eq!(board.get_moves().len(), 10);
+
+        // a pinned rook can still slide along the pin
+        let board = MailboxBoard::from_fen("4k3/4r3/8/8/8/8/4R3/4K3 w - - 0 1").unwrap();
+        let rook: Vec<Move> = board.legal_moves().into_iter().filter(|m| m.origin() == square("e2")).collect();
+        assert_eq!(targets(&rook), ["e3", "e4", "e5", "e6", "e7"]);
+
+        // a bishop pinned on a diagonal can slide along it, up to and including taking the pinner
+        let board = MailboxBoard::from_fen("4k3/8/8/q7/8/2B5/8/4K3 w - - 0 1").unwrap();
+        let bishop: Vec<Move> = board.legal_moves().into_iter().filter(|m| m.origin() == square("c3")).collect();
+        assert_eq!(targets(&bishop), ["a5", "b4", "d2"]);
+    }
+
+    #[test]
+    fn test_gives_check() {
+        let checks = |fen: &str, uci: &str| {
+            let board = MailboxBoard::from_fen(fen).unwrap();
+            board.gives_check(board.parse_uci(uci).unwrap())
+        };
+
+        // a rook checks directly along the king's rank
+        assert!(checks("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"));
+        assert!(!checks("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1b1"));
+
+        // the knight on e4 is in the way of the rook on e1, and stepping aside uncovers it
+        assert!(checks("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1", "e4c5"));
+        // the same goes for a pawn in front of a bishop
+        assert!(checks("7k/8/8/8/8/2P5/8/B5K1 w - - 0 1", "c3c4"));
+
+        assert!(checks("4k3/8/8/8/2N5/8/8/4K3 w - - 0 1", "c4d6"));
+        assert!(!checks("4k3/8/8/8/2N5/8/8/4K3 w - - 0 1", "c4e5"));
+        assert!(checks("4k3/8/3P4/8/8/8/8/4K3 w - - 0 1", "d6d7"));
+        assert!(checks("k7/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q"));
+        assert!(!checks("k7/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8n"));
+        // castling checks with the rook, and taking en passant can uncover a check along the rank
+        assert!(checks("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"));
+        assert!(checks("8/8/8/RPp4k/8/8/8/4K3 w - c6 0 1", "b5c6"));
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            let board = MailboxBoard::from_fen(fen).unwrap();
+            for m in board.legal_moves() {
+                let mut after = board.clone();
+                after.make_move(m);
+                assert_eq!(board.gives_check(m), after.in_check(after.side_to_move()), "{} {}", fen, m.to_uci());
+            }
+        }
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        let bits = |squares: &[&str]| squares.iter().fold(0, |bits, name| bits | 1 << square(name).index());
+
+        // two pieces between the king and the queen pin neither, until the pawn is gone
+        let board = MailboxBoard::from_fen("4k3/4r3/8/q7/8/2B5/3PN3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.pinned_pieces(Team::White), bits(&["e2"]));
+        let board = MailboxBoard::from_fen("4k3/4r3/8/q7/8/2B5/4N3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.pinned_pieces(Team::White), bits(&["c3", "e2"]));
+
+        // a knight can't pin, and a bishop can't pin along a file
+        let board = MailboxBoard::from_fen("4k3/4b3/8/8/5n2/8/4N3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.pinned_pieces(Team::White), 0);
+        assert_eq!(MailboxBoard::starting_position().pinned_pieces(Team::Black), 0);
+    }
+
+    #[test]
+    fn test_evasion_moves() {
+        // the rook on e8 checks along the file, so the knight can only block on e4
+        let board = MailboxBoard::from_fen("4r1k1/8/8/8/8/8/3N4/4K3 w - - 0 1").unwrap();
+        assert_eq!(targets(&board.evasion_moves()), ["d1", "e4", "f1", "f2"]);
+
+        // the checking knight can be taken by the rook, but not by the knight pinned by the queen
+        let board = MailboxBoard::from_fen("4k3/8/8/q7/8/5n2/3N4/4KR2 w - - 0 1").unwrap();
+        let moves = board.evasion_moves();
+        assert!(moves.iter().all(|m| m.origin() != square("d2")));
+        assert!(moves.iter().any(|m| m.origin() == square("f1") && m.target() == square("f3")));
+    }
+
+    #[test]
+    fn test_evasion_moves_double_check() {
+        // the rook and bishop both check, so the queen can't take either and only the king moves
+        let board = MailboxBoard::from_fen("4r1k1/8/8/8/7b/8/3N4/4K2Q w - - 0 1").unwrap();
+        let moves = board.evasion_moves();
+        assert!(moves.iter().all(|m| m.origin() == square("e1")));
+        assert_eq!(targets(&moves), ["d1", "f1"]);
+    }
+
+    #[test]
+    fn test_legal_moves_castling_through_check() {
+        // the bishop on c4 covers f1, so White can't castle kingside
+        let board = MailboxBoard::from_fen("4k3/8/8/8/2b5/8/8/4K2R w K - 0 1").unwrap();
+        assert!(board.get_moves().iter().any(|m| m.kind() == MoveKind::KingCastle));
+        assert!(board.legal_moves().iter().all(|m| m.kind() != MoveKind::KingCastle));
+
+        // no castling out of check either
+        let board = MailboxBoard::from_fen("4k3/8/8/8/8/8/4r3/R3K3 w Q - 0 1").unwrap();
+        assert!(board.legal_moves().iter().all(|m| m.kind() != MoveKind::QueenCastle));
+
+        // the rook passing over an attacked b1 is fine
+        let board = MailboxBoard::from_fen("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        assert!(board.legal_moves().iter().any(|m| m.kind() == MoveKind::QueenCastle));
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(MailboxBoard::starting_position().status(), GameStatus::Ongoing);
+
+        // fool's mate, 1. f3 e5 2. g4 Qh4#
+        let mut board = MailboxBoard::starting_position();
+        for uci in ["f2f3", "e7e5", "g2g4"] {
+            board.make_move(board.parse_uci(uci).unwrap());
+            assert_eq!(board.status(), GameStatus::Ongoing);
+        }
+        board.make_move(board.parse_uci("d8h4").unwrap());
+        assert_eq!(board.status(), GameStatus::Checkmate { winner: Team::Black });
+
+        let board = MailboxBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(!board.in_check(Team::Black));
+        assert_eq!(board.status(), GameStatus::Stalemate);
+
+        // same position with White to move is fine
+        let board = MailboxBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(board.status(), GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_random_move() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        // plays random moves from the start, checking each is legal where it's played
+        let play = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut board = MailboxBoard::starting_position();
+            let mut moves = Vec::new();
+
+            while let Some(m) = board.random_move(&mut rng).filter(|_| moves.len() < 40) {
+                assert!(board.legal_moves().contains(&m));
+                board.make_move(m);
+                moves.push(m);
+            }
+            moves
+        };
+
+        assert_eq!(play(7), play(7));
+        assert_ne!(play(7), play(8));
+
+        let mate = MailboxBoard::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(mate.random_move(&mut StdRng::seed_from_u64(7)), None);
+    }
+
+    #[test]
+    fn test_perft_starting_position() {
+        let mut board = MailboxBoard::starting_position();
+        let fen = board.to_fen();
+
+        assert_eq!(board.perft(0), 1);
+        assert_eq!(board.perft(1), 20);
+        assert_eq!(board.perft(2), 400);
+        assert_eq!(board.perft(3), 8902);
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_perft_tricky_positions() {
+        // "kiwipete", which exercises castling, en passant, promotions, and pins
+        let mut board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(board.perft(1), 48);
+        assert_eq!(board.perft(2), 2039);
+
+        let mut board = MailboxBoard::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(board.perft(1), 14);
+        assert_eq!(board.perft(2), 191);
+        assert_eq!(board.perft(3), 2812);
+
+        let mut board = MailboxBoard::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1").unwrap();
+        assert_eq!(board.perft(1), 6);
+        assert_eq!(board.perft(2), 264);
+        assert_eq!(board.perft(3), 9467);
+    }
+
+    #[test]
+    fn test_generate_moves_into() {
+        let board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let legal = board.legal_moves();
+        // whatever is already in the buffer is kept, with the moves added after it
+        let mut buf = vec![legal[0]];
+        board.generate_moves_into(&mut buf);
+        assert_eq!(buf[0], legal[0]);
+        assert_eq!(buf[1..], legal[..]);
+    }
+
+    #[test]
+    fn test_move_list() {
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+
+        let board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        board.generate_moves_into(&mut list);
+        assert_eq!(list.as_slice(), &board.legal_moves()[..]);
+        assert_eq!(list.iter().count(), 48);
+        list.clear();
+        assert_eq!(list.len(), 0);
+
+        // the most moves any position has, which has to fit without overflowing
+        let board = MailboxBoard::from_fen("R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1").unwrap();
+        let list = board.legal_move_list();
+        assert_eq!(list.len(), MAX_MOVES);
+        assert_eq!(list.as_slice(), &board.legal_moves()[..]);
+    }
+
+    #[test]
+    fn test_has_legal_move() {
+        let fools_mate = MailboxBoard::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+        let stalemate = MailboxBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(!fools_mate.has_legal_move());
+        assert!(!stalemate.has_legal_move());
+        assert_eq!(fools_mate.status(), GameStatus::Checkmate { winner: Team::Black });
+        assert_eq!(stalemate.status(), GameStatus::Stalemate);
+
+        // in check, with only the king able to move, and out of check
+        let checked = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+        let board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        for board in [&checked, &board] {
+            assert!(board.has_legal_move());
+            assert_eq!(board.status(), GameStatus::Ongoing);
+
+            // stopping at the first legal move leaves the rest untested, unlike generating them all
+            let (found, tested) = board.find_legal_move();
+            let tested_by_all = board.push_legal_moves(&mut Vec::new());
+            assert!(found);
+            assert!(tested < tested_by_all, "{} vs {}", tested, tested_by_all);
+            assert_eq!(tested, 1);
+        }
+
+        // with nothing legal, every move has to be tested either way
+        let (found, tested) = fools_mate.find_legal_move();
+        assert!(!found);
+        assert_eq!(tested, fools_mate.push_legal_moves(&mut Vec::new()));
+    }
+
+    #[test]
+    fn test_perft_divide() {
+        let mut board = MailboxBoard::starting_position();
+
+        let divide = board.perft_divide(2);
+        assert_eq!(divide.len(), 20);
+        assert!(divide.iter().all(|&(_, nodes)| nodes == 20));
+
+        let uci: Vec<String> = divide.iter().map(|(m, _)| m.to_uci()).collect();
+        let mut sorted = uci.clone();
+        sorted.sort();
+        assert_eq!(uci, sorted);
+
+        let expected = [
+            ("a2a3", 380), ("a2a4", 420), ("b1a3", 400), ("b1c3", 440), ("b2b3", 420),
+            ("b2b4", 421), ("c2c3", 420), ("c2c4", 441), ("d2d3", 539), ("d2d4", 560),
+            ("e2e3", 599), ("e2e4", 600), ("f2f3", 380), ("f2f4", 401), ("g1f3", 440),
+            ("g1h3", 400), ("g2g3", 420), ("g2g4", 421), ("h2h3", 380), ("h2h4", 420),
+        ];
+        let divide: Vec<(String, u64)> = board.perft_divide(3).into_iter().map(|(m, nodes)| (m.to_uci(), nodes)).collect();
+        assert_eq!(divide, expected.map(|(uci, nodes)| (uci.to_string(), nodes)));
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 8902);
+
+        assert!(board.perft_divide(0).is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::board::{MailboxBoard, Move};
+use crate::san::SanError;
+
+/// Errors from reading PGN movetext
+#[derive(Debug, PartialEq, Eq)]
+pub enum PgnError {
+    /// a `{` comment was never closed
+    UnclosedComment,
+    /// a move couldn't be played in the position it came up in
+    InvalidMove { san: String, error: SanError },
+}
+
+/// checks whether a token is a game result, which ends the movetext
+fn is_result(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+/// splits movetext into its move tokens, dropping comments, move numbers and annotations
+fn tokenize(movetext: &str) -> Result<Vec<&str>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut rest = movetext;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix('{') {
+            let end = comment.find('}').ok_or(PgnError::UnclosedComment)?;
+            rest = &comment[end + 1..];
+            continue;
+        }
+
+        let end = rest.find(|c: char| c.is_whitespace() || c == '{').unwrap_or(rest.len());
+        let (token, remaining) = rest.split_at(end);
+        rest = remaining.trim_start();
+
+        if is_result(token) {
+            break;
+        }
+
+        // a move number can be stuck to its move, like `1.e4` or `3...Nf6`
+        let token = match token.find(|c: char| !c.is_ascii_digit()) {
+            Some(start) if start > 0 && token[start..].starts_with('.') => token[start..].trim_start_matches('.'),
+            _ => token,
+        };
+        if !token.is_empty() && !token.starts_with('$') {
+            tokens.push(token);
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Plays the moves in PGN movetext, like `1. e4 e5 2. Nf3 {a comment} Nc6 1-0`, returning them in order
+///
+/// Move numbers, comments in braces, `$` annotations and the result are skipped. On an error the board is left
+/// after the last move that could be played.
+pub fn parse_moves(board: &mut MailboxBoard, movetext: &str) -> Result<Vec<Move>, PgnError> {
+    let mut moves = Vec::new();
+
+    for san in tokenize(movetext)? {
+        let m = board
+            .parse_san(san)
+            .map_err(|error| PgnError::InvalidMove { san: String::from(san), error })?;
+        board.make_move(m);
+        moves.push(m);
+    }
+
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_moves_scholars_mate() {
+        let mut board = MailboxBoard::starting_position();
+        let moves = parse_moves(&mut board, "1. e4 e5 2. Bc4 {aiming at f7} Nc6 3. Qh5 Nf6?? 4. Qxf7# 1-0").unwrap();
+
+        let ucis: Vec<String> = moves.iter().map(|m| m.to_uci()).collect();
+        assert_eq!(ucis, ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"]);
+        assert_eq!(board.to_fen(), "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4");
+    }
+
+    #[test]
+    fn test_parse_moves_formats() {
+        // move numbers stuck to moves, black's move numbers, annotations and no result
+        let mut board = MailboxBoard::starting_position();
+        let moves = parse_moves(&mut board, "1.d4 $1 1...d5\n2.c4{the Queen's Gambit}dxc4").unwrap();
+        assert_eq!(moves.len(), 4);
+        assert_eq!(board.to_fen(), "rnbqkbnr/ppp1pppp/8/8/2pP4/8/PP2PPPP/RNBQKBNR w KQkq - 0 3");
+
+        // nothing after the result is played
+        let mut board = MailboxBoard::starting_position();
+        assert_eq!(parse_moves(&mut board, "1. e4 * 2. e5").unwrap().len(), 1);
+        assert_eq!(parse_moves(&mut MailboxBoard::starting_position(), ""), Ok(Vec::new()));
+
+        // castling written with zeros isn't mistaken for a move number
+        let mut board = MailboxBoard::starting_position();
+        let moves = parse_moves(&mut board, "1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0").unwrap();
+        assert_eq!(moves.last().map(|m| m.to_uci()), Some(String::from("e1g1")));
+    }
+
+    #[test]
+    fn test_parse_moves_errors() {
+        let mut board = MailboxBoard::starting_position();
+        assert_eq!(parse_moves(&mut board, "1. e4 {unclosed"), Err(PgnError::UnclosedComment));
+
+        let mut board = MailboxBoard::starting_position();
+        assert_eq!(
+            parse_moves(&mut board, "1. e4 e5 2. Ke3"),
+            Err(PgnError::InvalidMove { san: String::from("Ke3"), error: SanError::IllegalMove })
+        );
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+    }
+}
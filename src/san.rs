@@ -0,0 +1,261 @@
+use crate::board::{Chessman, Coordinate, MailboxBoard, Move, MoveKind};
+
+/// Errors from parsing a move in standard algebraic notation
+#[derive(Debug, PartialEq, Eq)]
+pub enum SanError {
+    /// the text isn't shaped like a SAN move
+    InvalidSyntax,
+    /// no legal move matches the text
+    IllegalMove,
+    /// more than one legal move matches, so the origin needs to be given
+    AmbiguousMove,
+}
+
+/// gets the SAN letter for a chessman, pawns don't have one
+fn chessman_letter(chessman: Chessman) -> Option<char> {
+    match chessman {
+        Chessman::Pawn => None,
+        chessman => Some(chessman.to_char().to_ascii_uppercase()),
+    }
+}
+
+/// gets the chessman for a SAN letter, pawns don't have one
+fn chessman_from_letter(letter: char) -> Option<Chessman> {
+    // lowercase letters are files, like the `b` in `bxc3`
+    match Chessman::from_char(letter) {
+        Some(Chessman::Pawn) | None => None,
+        Some(_) if letter.is_ascii_lowercase() => None,
+        chessman => chessman,
+    }
+}
+
+impl MailboxBoard {
+    /// Writes a move in standard algebraic notation, like `Nf3`, `exd5`, `O-O` or `e8=Q+`
+    ///
+    /// The move should be legal in this position. The origin is only given when another piece of the same kind
+    /// could also move to the target, and a check or mate gets a `+` or `#` on the end.
+    pub fn to_san(&self, m: Move) -> String {
+        let mut san = String::new();
+
+        match m.kind() {
+            MoveKind::KingCastle => san.push_str("O-O"),
+            MoveKind::QueenCastle => san.push_str("O-O-O"),
+            kind => {
+                let chessman = m.piece().data().map_or(Chessman::Pawn, |(_, chessman)| chessman);
+                let is_capture = m.capture().data().is_some();
+
+                match chessman_letter(chessman) {
+                    Some(letter) => {
+                        san.push(letter);
+
+                        let rivals: Vec<Move> = self
+                            .legal_moves()
+                            .into_iter()
+                            .filter(|other| other.piece() == m.piece() && other.target() == m.target() && other.origin() != m.origin())
+                            .collect();
+
+                        if !rivals.is_empty() {
+                            let origin = m.origin().to_string();
+                            if rivals.iter().all(|other| other.origin().file() != m.origin().file()) {
+                                san.push_str(&origin[..1]);
+                            } else if rivals.iter().all(|other| other.origin().rank() != m.origin().rank()) {
+                                san.push_str(&origin[1..]);
+                            } else {
+                                san.push_str(&origin);
+                            }
+                        }
+                    }
+                    // pawn captures are the only ones that always name where they came from
+                    None if is_capture => san.push_str(&m.origin().to_string()[..1]),
+                    None => {}
+                }
+
+                if is_capture {
+                    san.push('x');
+                }
+                san.push_str(&m.target().to_string());
+
+                if let Some(letter) = kind.promotion_piece().and_then(chessman_letter) {
+                    san.push('=');
+                    san.push(letter);
+                }
+            }
+        }
+
+        if self.gives_check(m) {
+            let mut after = self.clone();
+            after.make_move(m);
+            san.push(if after.legal_moves().is_empty() { '#' } else { '+' });
+        }
+
+        san
+    }
+
+    /// Parses a move in standard algebraic notation, like `Nf3`, `O-O`, `exd6` or `e8=Q`, against the legal moves
+    ///
+    /// Check and mate markers are optional and not checked, and so is the `=` of a promotion, as in `e8Q`. Any origin file or rank given has to match, and
+    /// the move has to be the only legal one that fits.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let moves = self.legal_moves().into_iter();
+
+        let candidates: Vec<Move> = match san {
+            "O-O" | "0-0" => moves.filter(|m| m.kind() == MoveKind::KingCastle).collect(),
+            "O-O-O" | "0-0-0" => moves.filter(|m| m.kind() == MoveKind::QueenCastle).collect(),
+            _ => {
+                if !san.is_ascii() {
+                    return Err(SanError::InvalidSyntax);
+                }
+
+                let (san, promotes_to) = match san.split_once('=') {
+                    Some((san, letter)) => match letter.chars().collect::<Vec<_>>()[..] {
+                        [letter] => (san, Some(chessman_from_letter(letter).ok_or(SanError::InvalidSyntax)?)),
+                        _ => return Err(SanError::InvalidSyntax),
+                    },
+                    // some exporters leave out the `=`, but the target ends in a rank, so a piece letter after it can't be
+                    // anything else
+                    None => match san.chars().last().and_then(chessman_from_letter) {
+                        Some(chessman) => (&san[..san.len() - 1], Some(chessman)),
+                        None => (san, None),
+                    },
+                };
+
+                let (chessman, san) = match san.chars().next().and_then(chessman_from_letter) {
+                    Some(chessman) => (chessman, &san[1..]),
+                    None => (Chessman::Pawn, san),
+                };
+
+                if san.len() < 2 {
+                    return Err(SanError::InvalidSyntax);
+                }
+                let (hints, target) = san.split_at(san.len() - 2);
+                let target = Coordinate::from_algebraic(target).map_err(|_| SanError::InvalidSyntax)?;
+
+                let (hints, is_capture) = match hints.strip_suffix('x') {
+                    Some(hints) => (hints, true),
+                    None => (hints, false),
+                };
+                let (file, rank) = match *hints.as_bytes() {
+                    [] => (None, None),
+                    [file @ b'a'..=b'h'] => (Some(file - b'a'), None),
+                    [rank @ b'1'..=b'8'] => (None, Some(rank - b'1')),
+                    [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => (Some(file - b'a'), Some(rank - b'1')),
+                    _ => return Err(SanError::InvalidSyntax),
+                };
+
+                moves
+                    .filter(|m| m.piece().data().is_some_and(|(_, moving)| moving == chessman))
+                    .filter(|m| m.target() == target && m.kind().promotion_piece() == promotes_to)
+                    .filter(|m| file.is_none_or(|file| m.origin().file() == file))
+                    .filter(|m| rank.is_none_or(|rank| m.origin().rank() == rank))
+                    .filter(|m| !is_capture || m.capture().data().is_some())
+                    .collect()
+            }
+        };
+
+        match candidates[..] {
+            [m] => Ok(m),
+            [] => Err(SanError::IllegalMove),
+            _ => Err(SanError::AmbiguousMove),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// gets the SAN of a move given in UCI
+    fn san(fen: &str, uci: &str) -> String {
+        let board = MailboxBoard::from_fen(fen).unwrap();
+        board.to_san(board.parse_uci(uci).unwrap())
+    }
+
+    #[test]
+    fn test_to_san_simple() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(san(start, "e2e4"), "e4");
+        assert_eq!(san(start, "g1f3"), "Nf3");
+
+        assert_eq!(san("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", "e4d5"), "exd5");
+        assert_eq!(san("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), "exd6");
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1"), "O-O");
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8"), "O-O-O");
+    }
+
+    #[test]
+    fn test_to_san_promotion_and_check() {
+        assert_eq!(san("k7/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q"), "e8=Q+");
+        assert_eq!(san("k7/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8n"), "e8=N");
+        assert_eq!(san("3rk3/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8r"), "exd8=R+");
+
+        // Ra8 is a back rank mate
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+    }
+
+    #[test]
+    fn test_to_san_disambiguation() {
+        // both knights can reach d2, but they're on different files
+        assert_eq!(san("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1", "b1d2"), "Nbd2");
+        assert_eq!(san("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1", "f3d2"), "Nfd2");
+
+        // rooks on the same file are told apart by rank
+        assert_eq!(san("4k3/R7/8/8/8/8/8/R3K3 w - - 0 1", "a1a4"), "R1a4");
+
+        // with three queens, one shares a file and another a rank, so the whole square is needed
+        assert_eq!(san("4k3/8/8/8/Q2Q4/8/8/Q3K3 w - - 0 1", "a4d1"), "Qa4d1");
+        assert_eq!(san("4k3/8/8/8/Q2Q4/8/8/Q3K3 w - - 0 1", "a4b4"), "Qab4");
+
+        // a pinned knight can't move, so the other needs no disambiguation
+        assert_eq!(san("4k3/8/8/8/8/5N2/8/rN2K3 w - - 0 1", "f3d2"), "Nd2");
+    }
+
+    #[test]
+    fn test_parse_san_opening() {
+        let mut board = MailboxBoard::starting_position();
+
+        for token in "1. e4 e5 2. Nf3".split_whitespace().filter(|token| !token.ends_with('.')) {
+            let m = board.parse_san(token).expect(token);
+            board.make_move(m);
+        }
+
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        assert_eq!(board.fullmove_number(), 2);
+    }
+
+    #[test]
+    fn test_parse_san_forms() {
+        let board = MailboxBoard::from_fen("r3k2r/1P6/8/3pP3/8/5N2/8/RN2K2R w KQkq d6 0 1").unwrap();
+        let uci = |san: &str| board.parse_san(san).map(|m| m.to_uci());
+
+        assert_eq!(uci("O-O"), Ok(String::from("e1g1")));
+        // the knight on b1 is in the way
+        assert_eq!(uci("O-O-O"), Err(SanError::IllegalMove));
+        assert_eq!(uci("exd6"), Ok(String::from("e5d6")));
+        assert_eq!(uci("bxa8=Q+"), Ok(String::from("b7a8q")));
+        assert_eq!(uci("b8=N"), Ok(String::from("b7b8n")));
+        assert_eq!(uci("b8Q"), Ok(String::from("b7b8q")));
+        assert_eq!(uci("bxa8R+"), Ok(String::from("b7a8r")));
+        assert_eq!(uci("Nbd2"), Ok(String::from("b1d2")));
+        assert_eq!(uci("Nfxd2"), Err(SanError::IllegalMove));
+        assert_eq!(uci("N1d2"), Ok(String::from("b1d2")));
+        assert_eq!(uci("Nf3d2"), Ok(String::from("f3d2")));
+
+        assert_eq!(uci("Nd2"), Err(SanError::AmbiguousMove));
+        assert_eq!(uci("b8"), Err(SanError::IllegalMove));
+        assert_eq!(uci("Qd4"), Err(SanError::IllegalMove));
+        for bad in ["", "N", "Nz9", "b8=X", "b8X", "b8=", "Nbzd2", "e4?x"] {
+            assert_eq!(uci(bad), Err(SanError::InvalidSyntax), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_san_round_trip() {
+        let board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+
+        for m in board.legal_moves() {
+            let san = board.to_san(m);
+            assert_eq!(board.parse_san(&san), Ok(m), "{}", san);
+        }
+    }
+}
@@ -0,0 +1,1322 @@
+use crate::board::{Board, Chessman, Coordinate, MailboxBoard, Move, MoveKind, NUM_CHESSMEN, NUM_TEAMS, NUM_TILES, Piece, Team};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+use crate::eval;
+use crate::transposition::{Entry, Flag, TranspositionTable};
+
+/// The score for delivering checkmate right now, mates further away score a little less
+pub const MATE_SCORE: i32 = 100_000;
+
+/// A score beyond any real one, for the edges of a full alpha-beta window
+pub const INFINITY: i32 = MATE_SCORE + 1;
+
+/// Scores further from zero than this are mates
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1_000;
+
+/// turns a mate score counted from the root into one counted from the current position, so it can be stored
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > MATE_THRESHOLD => s + ply,
+        s if s < -MATE_THRESHOLD => s - ply,
+        s => s,
+    }
+}
+
+/// undoes `score_to_table` for a position `ply` moves from the root
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > MATE_THRESHOLD => s - ply,
+        s if s < -MATE_THRESHOLD => s + ply,
+        s => s,
+    }
+}
+
+/// the deepest a timed search goes, however much time it has
+#[cfg(feature = "std")]
+const MAX_DEPTH: u8 = 64;
+
+/// how many nodes are searched between looks at the clock
+#[cfg(feature = "std")]
+const NODES_PER_CLOCK_CHECK: u64 = 1024;
+
+/// how much shallower the search after a null move is than the normal one
+const NULL_MOVE_REDUCTION: u8 = 2;
+
+/// how far either side of the last iteration's score the next one's window starts
+const ASPIRATION_WINDOW: i32 = 50;
+
+/// checks whether a team has anything besides its king and pawns, without which passing is often better than
+/// any real move and null-move pruning goes wrong
+fn has_pieces(board: &MailboxBoard, team: Team) -> bool {
+    Coordinate::all().any(|square| {
+        board
+            .get_tile(square)
+            .data()
+            .is_some_and(|(owner, chessman)| owner == team && !matches!(chessman, Chessman::King | Chessman::Pawn))
+    })
+}
+
+/// gets the evaluation from the point of view of the side to move
+fn relative_eval(board: &MailboxBoard) -> i32 {
+    match board.side_to_move() {
+        Team::White => eval::evaluate(board),
+        Team::Black => -eval::evaluate(board),
+    }
+}
+
+/// gets how a capture ranks for searching, the most valuable victim first and then the least valuable attacker
+fn mvv_lva(board: &MailboxBoard, m: Move) -> Option<(i32, i32)> {
+    let (_, victim) = m.capture().data()?;
+    let attacker = match board.get_tile(m.origin()).data() {
+        Some((_, Chessman::King)) | None => i32::MAX,
+        Some((_, chessman)) => chessman.value(),
+    };
+    Some((victim.value(), -attacker))
+}
+
+/// finds the least valuable piece of a team attacking a square, kings last since they can't be recaptured
+fn least_valuable_attacker(board: &MailboxBoard, square: Coordinate, by: Team) -> Option<(Coordinate, Chessman)> {
+    board
+        .attackers(square, by)
+        .into_iter()
+        .filter_map(|attacker| board.get_tile(attacker).data().map(|(_, chessman)| (attacker, chessman)))
+        .min_by_key(|&(_, chessman)| match chessman {
+            Chessman::King => i32::MAX,
+            chessman => chessman.value(),
+        })
+}
+
+/// Works out the material a capture wins or loses once the exchange on its target square plays out
+///
+/// Each side recaptures with its least valuable attacker, including pieces uncovered behind the ones that
+/// have already taken, and either side can stop when carrying on would cost it. A king only recaptures when
+/// the square is no longer attacked. The move itself is always made, so a capture of a defended piece by a
+/// more valuable one comes out negative. Promotions are scored as if the pawn stayed a pawn.
+pub fn see(board: &MailboxBoard, m: Move) -> i32 {
+    let target = m.target();
+    let mut scratch = board.clone();
+    scratch.clear_tile(m.origin());
+    if m.kind() == MoveKind::EPCapture {
+        scratch.clear_tile(MailboxBoard::en_passant_victim(&m));
+    }
+
+    let mut gains = vec![m.capture().data().map_or(0, |(_, victim)| victim.value())];
+    let mut on_target = m.piece().data().map_or(0, |(_, chessman)| chessman.value());
+    let mut side = board.side_to_move().opponent();
+
+    while let Some((attacker, chessman)) = least_valuable_attacker(&scratch, target, side) {
+        if chessman == Chessman::King && !scratch.attackers(target, side.opponent()).is_empty() {
+            break;
+        }
+
+        gains.push(on_target - gains[gains.len() - 1]);
+        on_target = chessman.value();
+        scratch.clear_tile(attacker);
+        side = side.opponent();
+    }
+
+    // going backwards, each side only makes its capture if that beats stopping before it
+    for i in (1..gains.len()).rev() {
+        gains[i - 1] = -(-gains[i - 1]).max(gains[i]);
+    }
+    gains[0]
+}
+
+/// Sorts moves so captures come first, ordered by most valuable victim and then least valuable attacker
+///
+/// Quiet moves keep their order after the captures. Searching likely good captures first lets alpha-beta prune much more.
+pub fn order_moves(board: &MailboxBoard, moves: &mut [Move]) {
+    moves.sort_by_cached_key(|&m| Reverse(mvv_lva(board, m)));
+}
+
+/// Settings for `search`, covering how deep and long to search and which parts of the search to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// the deepest iteration to search to
+    pub max_depth: u8,
+    /// how long to keep deepening for, with no limit other than the depth if None
+    #[cfg(feature = "std")]
+    pub time_limit: Option<Duration>,
+    /// whether to try null-move pruning
+    pub null_move: bool,
+    /// whether to search captures past the leaves with `quiescence`, instead of scoring them as they stand
+    pub quiescence: bool,
+    /// whether to start each iteration with a narrow window around the last one's score
+    pub aspiration_windows: bool,
+    /// whether to search a ply deeper from positions where the side to move is in check
+    pub check_extensions: bool,
+}
+
+impl Default for SearchConfig {
+    /// Searches 4 plies deep with no time limit and everything turned on
+    fn default() -> Self {
+        SearchConfig {
+            max_depth: 4,
+            #[cfg(feature = "std")]
+            time_limit: None,
+            null_move: true,
+            quiescence: true,
+            aspiration_windows: true,
+            check_extensions: true,
+        }
+    }
+}
+
+/// State kept over the course of a search
+///
+/// Alpha-beta searches remember positions in the transposition table, which is empty unless given with `with_table`.
+/// They also remember killer moves, the quiet moves that caused cutoffs at each ply, and try them early in
+/// other positions at the same ply. Other quiet moves are tried in order of their history, a count of the cutoffs
+/// caused by the same piece moving to the same square anywhere in the search.
+#[derive(Debug)]
+pub struct Searcher {
+    nodes: u64,
+    table: TranspositionTable,
+    /// up to two killer moves for each ply, the most recent first
+    killers: Vec<[Option<Move>; 2]>,
+    use_killers: bool,
+    use_null_move: bool,
+    use_quiescence: bool,
+    use_aspiration_windows: bool,
+    use_check_extensions: bool,
+    /// cutoff counts for each piece and target square, weighted toward cutoffs further from the leaves
+    history: [[u32; NUM_TILES]; NUM_TEAMS * NUM_CHESSMEN],
+    /// the best line found from each ply of the current search, the root's being the principal variation
+    lines: Vec<Vec<Move>>,
+    /// when a timed search has to stop
+    #[cfg(feature = "std")]
+    deadline: Option<Instant>,
+    /// the node count at which the clock is next read
+    #[cfg(feature = "std")]
+    next_clock_check: u64,
+    /// set once the deadline passes, after which every node returns straight away
+    stopped: bool,
+}
+
+impl Default for Searcher {
+    fn default() -> Self {
+        Searcher {
+            nodes: 0,
+            table: TranspositionTable::default(),
+            killers: Vec::new(),
+            use_killers: true,
+            use_null_move: true,
+            use_quiescence: true,
+            use_aspiration_windows: true,
+            use_check_extensions: true,
+            history: [[0; NUM_TILES]; NUM_TEAMS * NUM_CHESSMEN],
+            lines: Vec::new(),
+            #[cfg(feature = "std")]
+            deadline: None,
+            #[cfg(feature = "std")]
+            next_clock_check: 0,
+            stopped: false,
+        }
+    }
+}
+
+impl Searcher {
+    /// Creates a searcher that hasn't searched anything yet
+    pub fn new() -> Self {
+        Searcher::default()
+    }
+
+    /// Creates a searcher that keeps results in a transposition table
+    pub fn with_table(table: TranspositionTable) -> Self {
+        Searcher { table, ..Searcher::default() }
+    }
+
+    /// gets the transposition table
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    /// gets the number of positions visited so far
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Gets the principal variation of the last search, the line of moves it expects both sides to play
+    ///
+    /// The line starts with the best move found and only goes as deep as the search did. It stops early where the
+    /// rest of the line came from the transposition table instead.
+    pub fn principal_variation(&self) -> Vec<Move> {
+        self.lines.first().cloned().unwrap_or_default()
+    }
+
+    /// checks now and then whether the deadline has passed, and stays stopped once it has
+    ///
+    /// The clock is read once at least `NODES_PER_CLOCK_CHECK` nodes have gone by since the last reading, however
+    /// many nodes were counted in between.
+    fn out_of_time(&mut self) -> bool {
+        #[cfg(feature = "std")]
+        if !self.stopped && self.nodes >= self.next_clock_check {
+            self.next_clock_check = self.nodes + NODES_PER_CLOCK_CHECK;
+            self.stopped = self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        }
+        self.stopped
+    }
+
+    /// forgets the line found from a ply, ready for searching a new position there
+    fn clear_line(&mut self, ply: i32) {
+        let ply = ply as usize;
+        if self.lines.len() <= ply + 1 {
+            self.lines.resize(ply + 2, Vec::new());
+        }
+        self.lines[ply].clear();
+    }
+
+    /// makes the line from a ply a move followed by the best line from the ply after
+    fn extend_line(&mut self, ply: i32, m: Move) {
+        let (line, rest) = self.lines.split_at_mut(ply as usize + 1);
+        let line = &mut line[ply as usize];
+        line.clear();
+        line.push(m);
+        line.extend_from_slice(&rest[0]);
+    }
+
+    /// turns the killer move heuristic on or off, it's on to begin with
+    pub fn set_killers(&mut self, enabled: bool) {
+        self.use_killers = enabled;
+    }
+
+    /// turns null-move pruning on or off, it's on to begin with
+    pub fn set_null_move(&mut self, enabled: bool) {
+        self.use_null_move = enabled;
+    }
+
+    /// turns the quiescence search at the leaves on or off, it's on to begin with
+    pub fn set_quiescence(&mut self, enabled: bool) {
+        self.use_quiescence = enabled;
+    }
+
+    /// turns aspiration windows in iterative deepening on or off, they're on to begin with
+    pub fn set_aspiration_windows(&mut self, enabled: bool) {
+        self.use_aspiration_windows = enabled;
+    }
+
+    /// turns check extensions on or off, they're on to begin with
+    pub fn set_check_extensions(&mut self, enabled: bool) {
+        self.use_check_extensions = enabled;
+    }
+
+    /// scores a leaf, settling any captures first unless quiescence is turned off
+    fn leaf(&mut self, board: &mut MailboxBoard, alpha: i32, beta: i32) -> i32 {
+        if self.use_quiescence {
+            self.quiescence(board, alpha, beta)
+        } else {
+            self.nodes += 1;
+            relative_eval(board)
+        }
+    }
+
+    /// gets the history count for a piece moving to a square, which grows with each cutoff it causes
+    pub fn history(&self, piece: Piece, target: Coordinate) -> u32 {
+        match piece.data() {
+            Some((team, chessman)) => self.history[team as usize * NUM_CHESSMEN + chessman as usize][target.index() as usize],
+            None => 0,
+        }
+    }
+
+    /// counts a cutoff by a quiet move, deeper searches counting for more since their cutoffs save more work
+    fn record_history(&mut self, m: Move, depth: u8) {
+        if let Some((team, chessman)) = m.piece().data() {
+            let count = &mut self.history[team as usize * NUM_CHESSMEN + chessman as usize][m.target().index() as usize];
+            *count = count.saturating_add(u32::from(depth) * u32::from(depth));
+        }
+    }
+
+    /// sorts moves for searching at a ply: captures by MVV-LVA, then killers, then other quiet moves by history
+    fn order(&self, board: &MailboxBoard, moves: &mut [Move], ply: i32) {
+        order_moves(board, moves);
+
+        let quiet_start = moves.iter().position(|m| m.capture().data().is_none()).unwrap_or(moves.len());
+        moves[quiet_start..].sort_by_cached_key(|&m| Reverse(self.history(m.piece(), m.target())));
+
+        // quiet moves that refuted other positions at this ply often refute this one too
+        if self.use_killers {
+            for killer in self.killers(ply).iter().rev().flatten() {
+                if let Some(i) = moves[quiet_start..].iter().position(|m| m == killer) {
+                    moves[quiet_start..=quiet_start + i].rotate_right(1);
+                }
+            }
+        }
+    }
+
+    /// gets the killer moves remembered for a ply
+    fn killers(&self, ply: i32) -> [Option<Move>; 2] {
+        self.killers.get(ply as usize).copied().unwrap_or_default()
+    }
+
+    /// remembers a quiet move that caused a cutoff at a ply, pushing out the older of the two killers
+    fn store_killer(&mut self, m: Move, ply: i32) {
+        let ply = ply as usize;
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(m) {
+            *killers = [Some(m), killers[0]];
+        }
+    }
+
+    /// Searches every line to a fixed depth, see `negamax`
+    pub fn negamax(&mut self, board: &mut MailboxBoard, depth: u8) -> i32 {
+        self.negamax_ply(board, depth, 0)
+    }
+
+    /// negamax that knows how far it is from the root, so nearer mates can score higher
+    fn negamax_ply(&mut self, board: &mut MailboxBoard, depth: u8, ply: i32) -> i32 {
+        if depth == 0 {
+            return self.leaf(board, -INFINITY, INFINITY);
+        }
+        self.nodes += 1;
+
+        let moves = board.legal_moves();
+        if moves.is_empty() {
+            return if board.in_check(board.side_to_move()) { -(MATE_SCORE - ply) } else { 0 };
+        }
+
+        let mut best = -INFINITY;
+        for m in moves {
+            let undo = board.make_move(m);
+            let score = -self.negamax_ply(board, depth - 1, ply + 1);
+            board.unmake_move(undo);
+
+            best = best.max(score);
+        }
+
+        best
+    }
+
+    /// Searches to a fixed depth with alpha-beta pruning, see `alpha_beta`
+    pub fn alpha_beta(&mut self, board: &mut MailboxBoard, depth: u8, alpha: i32, beta: i32) -> i32 {
+        self.alpha_beta_ply(board, depth, alpha, beta, 0, true)
+    }
+
+    /// fail-soft alpha-beta that knows how far it is from the root, and whether it may try a null move
+    fn alpha_beta_ply(
+        &mut self,
+        board: &mut MailboxBoard,
+        depth: u8,
+        mut alpha: i32,
+        beta: i32,
+        ply: i32,
+        allow_null: bool,
+    ) -> i32 {
+        self.clear_line(ply);
+
+        // going back to a position from earlier in the game or the search can be kept up forever, so it's a draw
+        if ply > 0 && board.is_repetition() {
+            return 0;
+        }
+
+        // there are few ways out of check, so looking a ply further is cheap and keeps mating attacks in view
+        let mover = board.side_to_move();
+        let in_check = board.in_check(mover);
+        let depth = if in_check && self.use_check_extensions { depth + 1 } else { depth };
+
+        if depth == 0 {
+            return self.leaf(board, alpha, beta);
+        }
+        self.nodes += 1;
+        // the score doesn't matter once out of time, since the whole iteration gets thrown away
+        if self.out_of_time() {
+            return 0;
+        }
+
+        let hash = board.zobrist_hash();
+        let mut hash_move = None;
+        if let Some(entry) = self.table.probe(hash) {
+            hash_move = entry.best_move();
+
+            if entry.depth() >= depth {
+                let score = score_from_table(entry.score(), ply);
+                match entry.flag() {
+                    Flag::Exact => return score,
+                    Flag::LowerBound if score >= beta => return score,
+                    Flag::UpperBound if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        // if handing the opponent a free move still fails high, a real move surely would too, which is
+        // only wrong when every move makes things worse, so not in check or with just a king and pawns;
+        // a pass never proves a mate, so it sits out searches for mate scores
+        if allow_null
+            && self.use_null_move
+            && depth > NULL_MOVE_REDUCTION
+            && !in_check
+            && beta.abs() <= MATE_THRESHOLD
+            && has_pieces(board, mover)
+        {
+            let en_passant = board.en_passant();
+            board.set_en_passant(None);
+            board.set_side_to_move(mover.opponent());
+            let score = -self.alpha_beta_ply(board, depth - 1 - NULL_MOVE_REDUCTION, -beta, -beta + 1, ply + 1, false);
+            board.set_side_to_move(mover);
+            board.set_en_passant(en_passant);
+
+            if score >= beta {
+                return score.min(MATE_THRESHOLD);
+            }
+        }
+
+        let mut moves = board.legal_moves();
+        if moves.is_empty() {
+            return if in_check { -(MATE_SCORE - ply) } else { 0 };
+        }
+        self.order(board, &mut moves, ply);
+
+        // the best move from an earlier search is the most likely to cause a cutoff
+        if let Some(i) = hash_move.and_then(|hash_move| moves.iter().position(|&m| m == hash_move)) {
+            moves[..=i].rotate_right(1);
+        }
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        for m in moves {
+            let undo = board.make_move(m);
+            let score = -self.alpha_beta_ply(board, depth - 1, -beta, -alpha, ply + 1, true);
+            board.unmake_move(undo);
+
+            if score > best {
+                best = score;
+                best_move = Some(m);
+                if score > alpha {
+                    self.extend_line(ply, m);
+                }
+                alpha = alpha.max(score);
+                if alpha >= beta {
+                    // a stopped search's scores are made up, so its cutoffs say nothing about the move
+                    if m.capture().data().is_none() && !self.stopped {
+                        self.record_history(m, depth);
+                        if self.use_killers {
+                            self.store_killer(m, ply);
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        let flag = if best <= original_alpha {
+            Flag::UpperBound
+        } else if best >= beta {
+            Flag::LowerBound
+        } else {
+            Flag::Exact
+        };
+        if !self.stopped {
+            self.table.store(Entry::new(hash, depth, score_to_table(best, ply), flag, best_move));
+        }
+
+        best
+    }
+
+    /// Searches only captures until the position is quiet, see `quiescence`
+    pub fn quiescence(&mut self, board: &mut MailboxBoard, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+
+        // the side to move doesn't have to capture, so the static evaluation is a lower bound
+        let stand_pat = relative_eval(board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut captures: Vec<Move> = board.legal_moves().into_iter().filter(|m| m.capture().data().is_some()).collect();
+        order_moves(board, &mut captures);
+
+        let mut best = stand_pat;
+        for m in captures {
+            let undo = board.make_move(m);
+            let score = -self.quiescence(board, -beta, -alpha);
+            board.unmake_move(undo);
+
+            if score > best {
+                best = score;
+                alpha = alpha.max(score);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        best
+    }
+
+    /// searches each root move in order, returning the index of the best one and its score
+    fn search_root(&mut self, board: &mut MailboxBoard, moves: &[Move], depth: u8) -> Option<(usize, i32)> {
+        self.search_root_window(board, moves, depth, -INFINITY, INFINITY)
+    }
+
+    /// searches the root moves inside a window, stopping at the first move that reaches beta
+    ///
+    /// Like the rest of the search this is fail-soft, so a score at or below alpha or at or above beta is only a bound.
+    fn search_root_window(
+        &mut self,
+        board: &mut MailboxBoard,
+        moves: &[Move],
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<(usize, i32)> {
+        let mut best: Option<(usize, i32)> = None;
+        self.clear_line(0);
+
+        for (i, &m) in moves.iter().enumerate() {
+            let alpha = best.map_or(alpha, |(_, score)| score.max(alpha));
+
+            let undo = board.make_move(m);
+            let score = -self.alpha_beta_ply(board, depth.saturating_sub(1), -beta, -alpha, 1, true);
+            board.unmake_move(undo);
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((i, score));
+                self.extend_line(0, m);
+                if score >= beta {
+                    break;
+                }
+            }
+        }
+
+        self.nodes += 1;
+        best
+    }
+
+    /// searches one iteration of iterative deepening, starting from a window around the last iteration's score
+    ///
+    /// Whichever side of the window the score falls outside of is opened all the way and the root searched again,
+    /// until the score lands inside it.
+    fn search_iteration(
+        &mut self,
+        board: &mut MailboxBoard,
+        moves: &[Move],
+        depth: u8,
+        previous: Option<i32>,
+    ) -> Option<(usize, i32)> {
+        let (mut alpha, mut beta) = match previous {
+            Some(score) if self.use_aspiration_windows => (score - ASPIRATION_WINDOW, score + ASPIRATION_WINDOW),
+            _ => (-INFINITY, INFINITY),
+        };
+
+        loop {
+            let (i, score) = self.search_root_window(board, moves, depth, alpha, beta)?;
+            if self.stopped {
+                return Some((i, score));
+            }
+
+            if score <= alpha && alpha > -INFINITY {
+                alpha = -INFINITY;
+            } else if score >= beta && beta < INFINITY {
+                beta = INFINITY;
+            } else {
+                return Some((i, score));
+            }
+        }
+    }
+
+    /// Picks the move with the best score, see `best_move`
+    pub fn best_move(&mut self, board: &mut MailboxBoard, depth: u8) -> Option<Move> {
+        let mut moves = board.legal_moves();
+        order_moves(board, &mut moves);
+        self.search_root(board, &moves, depth).map(|(i, _)| moves[i])
+    }
+
+    /// Searches at each depth up to the maximum, see `search_iterative`
+    pub fn search_iterative(&mut self, board: &mut MailboxBoard, max_depth: u8) -> Option<(Move, i32)> {
+        let mut moves = board.legal_moves();
+        order_moves(board, &mut moves);
+        let mut best = None;
+
+        for depth in 1..=max_depth.max(1) {
+            let (i, score) = self.search_iteration(board, &moves, depth, best.map(|(_, score)| score))?;
+
+            // searching the best move first next time makes for a tighter window sooner
+            moves[..=i].rotate_right(1);
+            best = Some((moves[0], score));
+        }
+
+        best
+    }
+
+    /// Searches deeper and deeper until the time runs out, see `search_timed`
+    #[cfg(feature = "std")]
+    pub fn search_timed(&mut self, board: &mut MailboxBoard, limit: Duration) -> Option<(Move, i32)> {
+        self.search_timed_to(board, MAX_DEPTH, limit)
+    }
+
+    /// Searches with the settings in a config, see `search`
+    ///
+    /// The config only applies to this search, afterwards the searcher goes back to the settings it had before.
+    pub fn search(&mut self, board: &mut MailboxBoard, config: &SearchConfig) -> Option<(Move, i32)> {
+        let saved = (self.use_null_move, self.use_quiescence, self.use_aspiration_windows, self.use_check_extensions);
+        self.use_null_move = config.null_move;
+        self.use_quiescence = config.quiescence;
+        self.use_aspiration_windows = config.aspiration_windows;
+        self.use_check_extensions = config.check_extensions;
+
+        #[cfg(feature = "std")]
+        let best = match config.time_limit {
+            Some(limit) => self.search_timed_to(board, config.max_depth, limit),
+            None => self.search_iterative(board, config.max_depth),
+        };
+        #[cfg(not(feature = "std"))]
+        let best = self.search_iterative(board, config.max_depth);
+
+        (self.use_null_move, self.use_quiescence, self.use_aspiration_windows, self.use_check_extensions) = saved;
+        best
+    }
+
+    /// deepens until the time runs out or a depth is reached, whichever comes first
+    #[cfg(feature = "std")]
+    fn search_timed_to(&mut self, board: &mut MailboxBoard, max_depth: u8, limit: Duration) -> Option<(Move, i32)> {
+        let start = Instant::now();
+        let mut moves = board.legal_moves();
+        order_moves(board, &mut moves);
+        let mut best = None;
+        let mut line = Vec::new();
+
+        self.stopped = false;
+        for depth in 1..=max_depth.max(1) {
+            let (i, score) = self.search_iteration(board, &moves, depth, best.map(|(_, score)| score))?;
+            if self.stopped {
+                // the unfinished depth overwrote the line, so it goes back to the one for the move being returned
+                self.lines[0] = line;
+                break;
+            }
+
+            moves[..=i].rotate_right(1);
+            best = Some((moves[0], score));
+            line = self.principal_variation();
+
+            // the first depth always finishes, so there's a move to play however short the limit
+            self.deadline = Some(start + limit);
+            self.next_clock_check = self.nodes;
+            if start.elapsed() >= limit || score.abs() > MATE_THRESHOLD {
+                break;
+            }
+        }
+
+        self.deadline = None;
+        self.stopped = false;
+        best
+    }
+}
+
+/// Searches every line to a fixed depth, returning the score from the side to move's point of view
+///
+/// The leaves are scored with `quiescence`. Being checkmated scores `-MATE_SCORE` plus the number of moves until it happens, and stalemate scores 0.
+pub fn negamax(board: &mut MailboxBoard, depth: u8) -> i32 {
+    Searcher::new().negamax(board, depth)
+}
+
+/// Searches to a fixed depth like `negamax`, but skips lines that can't fall inside the alpha-beta window
+///
+/// Scores inside the window are exact. A score at or below alpha is an upper bound, and one at or above beta is
+/// a lower bound. Use `-INFINITY` and `INFINITY` to always get the exact score.
+///
+/// Positions where the side to move is in check are searched a ply deeper than the rest, so this can see mates
+/// that `negamax` at the same depth misses.
+pub fn alpha_beta(board: &mut MailboxBoard, depth: u8, alpha: i32, beta: i32) -> i32 {
+    Searcher::new().alpha_beta(board, depth, alpha, beta)
+}
+
+/// Searches captures from a position until there are none left worth making, returning a fail-soft score
+///
+/// The fixed-depth searches call this at their leaves, so they don't stop in the middle of an exchange.
+pub fn quiescence(board: &mut MailboxBoard, alpha: i32, beta: i32) -> i32 {
+    Searcher::new().quiescence(board, alpha, beta)
+}
+
+/// Picks the move with the best score, or None if there are no legal moves
+///
+/// When several moves score the same the first one searched is kept, see `order_moves`.
+pub fn best_move(board: &mut MailboxBoard, depth: u8) -> Option<Move> {
+    Searcher::new().best_move(board, depth)
+}
+
+/// Runs alpha-beta at depths 1 up to `max_depth`, returning the best move and its score from the deepest one
+///
+/// Each iteration searches the previous best move first, inside a narrow window around the previous score that's
+/// opened up again if the score falls outside it. Returns None if there are no legal moves.
+pub fn search_iterative(board: &mut MailboxBoard, max_depth: u8) -> Option<(Move, i32)> {
+    Searcher::new().search_iterative(board, max_depth)
+}
+
+/// Runs iterative deepening until a time limit, returning the best move and score from the last depth it finished
+///
+/// The clock is checked every so often during the search, and a depth that's still going when time runs out is
+/// abandoned. The first depth is always finished, so this can run over a very short limit. It also stops early
+/// on finding a forced mate. Returns None if there are no legal moves.
+#[cfg(feature = "std")]
+pub fn search_timed(board: &mut MailboxBoard, limit: Duration) -> Option<(Move, i32)> {
+    Searcher::new().search_timed(board, limit)
+}
+
+/// Searches each root move on its own thread to a fixed depth, returning the best move and its exact score
+///
+/// The threads share the best score found so far, which every root move still to be searched takes as its alpha,
+/// so a move that can't beat it is cut off early like in a serial search. The window is kept one below that
+/// score, so a move that only ties it still gets its exact score and ties go to the move `order_moves` puts
+/// first. Each thread has its own `Searcher`, though, and the transposition table isn't shared between them.
+/// Returns None if there are no legal moves.
+#[cfg(feature = "parallel")]
+pub fn search_parallel(board: &MailboxBoard, depth: u8) -> Option<(Move, i32)> {
+    use core::sync::atomic::{AtomicI32, Ordering};
+    use rayon::prelude::*;
+
+    let mut moves = board.legal_moves();
+    order_moves(board, &mut moves);
+    let best_score = AtomicI32::new(-INFINITY);
+
+    let scores: Vec<i32> = moves
+        .par_iter()
+        .map(|&m| {
+            let mut board = board.clone();
+            board.make_move(m);
+
+            let alpha = (best_score.load(Ordering::Relaxed) - 1).max(-INFINITY);
+            let score = -Searcher::new().alpha_beta_ply(&mut board, depth.saturating_sub(1), -INFINITY, -alpha, 1, true);
+            best_score.fetch_max(score, Ordering::Relaxed);
+            score
+        })
+        .collect();
+
+    moves.into_iter().zip(scores).fold(None, |best, (m, score)| match best {
+        Some((_, best_score)) if score <= best_score => best,
+        _ => Some((m, score)),
+    })
+}
+
+/// Searches a position with iterative deepening as set up by a config, returning the best move and its score
+///
+/// This goes up to the config's maximum depth, stopping sooner if it has a time limit that runs out first, as
+/// `search_timed` does. Returns None if there are no legal moves.
+pub fn search(board: &mut MailboxBoard, config: &SearchConfig) -> Option<(Move, i32)> {
+    Searcher::new().search(board, config)
+}
+
+/// Plays a game from the starting position with `best_move` choosing every move for both sides
+///
+/// The game stops at checkmate or stalemate, after `max_moves` moves, or once the position is a draw by
+/// repetition, the fifty-move rule or insufficient material. Returns the moves played in order.
+pub fn self_play(depth: u8, max_moves: usize) -> Vec<Move> {
+    let mut board = MailboxBoard::starting_position();
+    let mut searcher = Searcher::new();
+    let mut moves = Vec::new();
+
+    while moves.len() < max_moves
+        && !board.is_threefold_repetition()
+        && !board.is_fifty_move_draw()
+        && !board.is_insufficient_material()
+    {
+        let Some(m) = searcher.best_move(&mut board, depth) else { break };
+        board.make_move(m);
+        moves.push(m);
+    }
+
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movegen::GameStatus;
+
+    #[test]
+    fn test_negamax_mate_in_one() {
+        // Ra8 is a back rank mate
+        let mut board = MailboxBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let fen = board.to_fen();
+
+        assert_eq!(negamax(&mut board, 2), MATE_SCORE - 1);
+        assert_eq!(board.to_fen(), fen);
+
+        // from Black's side the same position after Ra8 is lost
+        let mut mated = MailboxBoard::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 1").unwrap();
+        assert_eq!(negamax(&mut mated, 1), -MATE_SCORE);
+    }
+
+    #[test]
+    fn test_negamax_terminal_and_leaf() {
+        let mut stalemate = MailboxBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(negamax(&mut stalemate, 3), 0);
+
+        // depth 0 is just the evaluation, flipped for Black
+        let mut board = MailboxBoard::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert_eq!(negamax(&mut board, 0), -eval::evaluate(&board));
+
+        // White can win the hanging queen
+        let mut board = MailboxBoard::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        assert!(negamax(&mut board, 2) > 0);
+    }
+
+    #[test]
+    fn test_best_move_mate_in_one() {
+        for fen in [
+            "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            // Black to move, Qh4 is fool's mate
+            "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2",
+        ] {
+            let mut board = MailboxBoard::from_fen(fen).unwrap();
+            let mover = board.side_to_move();
+
+            let m = best_move(&mut board, 2).expect("no move found");
+            board.make_move(m);
+            assert_eq!(board.status(), GameStatus::Checkmate { winner: mover }, "{} {}", fen, m.to_uci());
+        }
+    }
+
+    #[test]
+    fn test_best_move_no_moves() {
+        let mut board = MailboxBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(best_move(&mut board, 2), None);
+
+        let mut board = MailboxBoard::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        assert_eq!(best_move(&mut board, 2).map(|m| m.to_uci()), Some(String::from("d2d5")));
+    }
+
+    #[test]
+    fn test_alpha_beta_matches_negamax() {
+        let mut board = MailboxBoard::from_fen("4k3/1pp2p2/8/3n4/4P3/2N5/1PP2P2/4K3 w - - 0 1").unwrap();
+
+        // negamax scores leaves as they stand and never extends checks or passes, so alpha-beta mustn't either
+        let plain_searcher = || {
+            let mut searcher = Searcher::new();
+            searcher.set_quiescence(false);
+            searcher.set_check_extensions(false);
+            searcher.set_null_move(false);
+            searcher
+        };
+
+        let mut plain = plain_searcher();
+        let expected = plain.negamax(&mut board, 4);
+
+        let mut pruned = plain_searcher();
+        assert_eq!(pruned.alpha_beta(&mut board, 4, -INFINITY, INFINITY), expected);
+        assert!(pruned.nodes() * 4 < plain.nodes(), "{} vs {}", pruned.nodes(), plain.nodes());
+
+        // scores outside the window come back as bounds on the right side, which can land on the window's edge
+        let above = plain_searcher().alpha_beta(&mut board, 4, expected + 1, expected + 50);
+        assert!((expected..=expected + 1).contains(&above), "{} vs {}", above, expected);
+        let below = plain_searcher().alpha_beta(&mut board, 4, expected - 50, expected - 1);
+        assert!((expected - 1..=expected).contains(&below), "{} vs {}", below, expected);
+    }
+
+    #[test]
+    fn test_search_iterative() {
+        // the hanging queen should be taken at every depth, and the pawns keep the rooks from giving check instead
+        let mut board = MailboxBoard::from_fen("k7/pp6/8/3q4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        let mut previous = None;
+
+        for depth in 1..=4 {
+            let (m, score) = search_iterative(&mut board, depth).expect("no move found");
+            assert_eq!(m.to_uci(), "d2d5", "depth {}", depth);
+            assert!(score > 0);
+
+            if let Some(previous) = previous {
+                assert_eq!(m, previous);
+            }
+            previous = Some(m);
+        }
+
+        // agrees with a plain search at the same depth
+        let (_, score) = search_iterative(&mut board, 3).unwrap();
+        assert_eq!(score, alpha_beta(&mut board, 3, -INFINITY, INFINITY));
+
+        let mut stalemate = MailboxBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(search_iterative(&mut stalemate, 3), None);
+    }
+
+    #[test]
+    fn test_quiescence() {
+        // Black's queen is hanging to the pawn, which the static evaluation doesn't know about
+        let mut board = MailboxBoard::from_fen("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let static_eval = relative_eval(&board);
+        assert!(static_eval < -500);
+        assert!(quiescence(&mut board, -INFINITY, INFINITY) > 0);
+
+        // a quiet position is just its evaluation
+        let mut board = MailboxBoard::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(quiescence(&mut board, -INFINITY, INFINITY), relative_eval(&board));
+    }
+
+    #[test]
+    fn test_quiescence_sees_recaptures() {
+        // Qxd5 grabs a pawn at depth 1, but the e6 pawn takes the queen back
+        let mut board = MailboxBoard::from_fen("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let qxd5 = board.parse_uci("d1d5").unwrap();
+
+        let undo = board.make_move(qxd5);
+        let naive = -relative_eval(&board);
+        let quiet = -quiescence(&mut board, -INFINITY, INFINITY);
+        board.unmake_move(undo);
+
+        let before = relative_eval(&board);
+        assert!(naive > before);
+        assert!(quiet < before - 500);
+        assert_ne!(best_move(&mut board, 1), Some(qxd5));
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let mut board = MailboxBoard::from_fen("r3k3/1pp2p2/8/3n4/4P3/2N5/1PP2P2/R3K3 w Qq - 0 1").unwrap();
+        let expected = alpha_beta(&mut board, 3, -INFINITY, INFINITY);
+
+        let mut searcher = Searcher::with_table(TranspositionTable::new(1 << 16));
+        assert_eq!(searcher.alpha_beta(&mut board, 3, -INFINITY, INFINITY), expected);
+        let cold = searcher.nodes();
+
+        // the root is already in the table, so the second search finishes straight away
+        assert_eq!(searcher.alpha_beta(&mut board, 3, -INFINITY, INFINITY), expected);
+        let warm = searcher.nodes() - cold;
+        assert!(warm * 100 < cold, "{} vs {}", warm, cold);
+
+        // a deeper search gets to reuse the shallower results for move ordering
+        let deeper = alpha_beta(&mut board, 4, -INFINITY, INFINITY);
+        assert_eq!(searcher.alpha_beta(&mut board, 4, -INFINITY, INFINITY), deeper);
+    }
+
+    #[test]
+    fn test_transposition_table_mates() {
+        // mate scores come back counted from the root they're probed at
+        let mut board = MailboxBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut searcher = Searcher::with_table(TranspositionTable::new(1 << 12));
+
+        assert_eq!(searcher.alpha_beta(&mut board, 3, -INFINITY, INFINITY), MATE_SCORE - 1);
+        assert_eq!(searcher.alpha_beta(&mut board, 3, -INFINITY, INFINITY), MATE_SCORE - 1);
+        assert_eq!(searcher.search_iterative(&mut board, 3).map(|(m, _)| m.to_uci()), Some(String::from("a1a8")));
+    }
+
+    #[test]
+    fn test_order_moves() {
+        // Qxa4 takes a queen and exf5 only a knight, though the pawn is the cheaper attacker
+        let board = MailboxBoard::from_fen("4k3/8/8/5n2/q3P3/8/8/3QK3 w - - 0 1").unwrap();
+        let mut moves = board.legal_moves();
+        order_moves(&board, &mut moves);
+
+        let ucis: Vec<String> = moves.iter().map(|m| m.to_uci()).collect();
+        assert_eq!(ucis[..2], ["d1a4", "e4f5"]);
+        assert!(moves[2..].iter().all(|m| m.capture().data().is_none()));
+
+        // among captures of the same piece the cheaper attacker goes first
+        let board = MailboxBoard::from_fen("4k3/8/8/3r4/2P5/8/8/3RK3 w - - 0 1").unwrap();
+        let mut moves = board.legal_moves();
+        order_moves(&board, &mut moves);
+        assert_eq!(moves[0].to_uci(), "c4d5");
+        assert_eq!(moves[1].to_uci(), "d1d5");
+    }
+
+    #[test]
+    fn test_self_play() {
+        let moves = self_play(1, 60);
+        assert!(!moves.is_empty() && moves.len() <= 60);
+
+        let mut board = MailboxBoard::starting_position();
+        for m in moves {
+            assert!(board.legal_moves().contains(&m), "{}", m.to_uci());
+            board.make_move(m);
+        }
+
+        assert!(self_play(2, 0).is_empty());
+    }
+
+    #[test]
+    fn test_see() {
+        let see_uci = |fen: &str, uci: &str| {
+            let board = MailboxBoard::from_fen(fen).unwrap();
+            see(&board, board.parse_uci(uci).unwrap())
+        };
+
+        // the queen takes a pawn and is taken back by another
+        assert_eq!(see_uci("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1", "d2d5"), 100 - 900);
+        // an undefended pawn is just won
+        assert_eq!(see_uci("4k3/8/8/3p4/8/8/3Q4/4K3 w - - 0 1", "d2d5"), 100);
+        // rook takes rook, queen takes back, and the second rook behind the first takes the queen
+        assert_eq!(see_uci("3qk3/8/8/3r4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 500);
+        // the king can't take back while the square is still covered
+        assert_eq!(see_uci("8/8/4k3/3p4/4P3/8/8/3RK3 w - - 0 1", "e4d5"), 100);
+        assert_eq!(see_uci("8/8/4k3/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 0);
+        assert_eq!(see_uci("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    }
+
+    #[test]
+    fn test_killer_moves() {
+        let search = |use_killers: bool| {
+            let mut board = MailboxBoard::starting_position();
+            let mut searcher = Searcher::new();
+            searcher.set_killers(use_killers);
+
+            let score = searcher.alpha_beta(&mut board, 4, -INFINITY, INFINITY);
+            (score, searcher.nodes())
+        };
+
+        let (score, nodes) = search(true);
+        let (plain_score, plain_nodes) = search(false);
+        assert_eq!(score, plain_score);
+        assert!(nodes < plain_nodes, "{} nodes with killers, {} without", nodes, plain_nodes);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut board = MailboxBoard::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+        let mut searcher = Searcher::new();
+        searcher.alpha_beta(&mut board, 3, -INFINITY, INFINITY);
+
+        let moves = board.legal_moves();
+        let counts: Vec<u32> = moves.iter().map(|m| searcher.history(m.piece(), m.target())).collect();
+        assert!(counts.iter().any(|&count| count > 0));
+
+        // with no killers this far from the root, the quiet moves come out in order of history
+        let mut ordered = moves.clone();
+        searcher.order(&board, &mut ordered, 100);
+        let quiet: Vec<u32> = ordered
+            .iter()
+            .filter(|m| m.capture().data().is_none())
+            .map(|m| searcher.history(m.piece(), m.target()))
+            .collect();
+        assert!(quiet.windows(2).all(|pair| pair[0] >= pair[1]), "{:?}", quiet);
+        assert!(ordered[..moves.len() - quiet.len()].iter().all(|m| m.capture().data().is_some()));
+    }
+
+    #[test]
+    fn test_null_move_pruning() {
+        // Black's queen is hanging to the knight
+        let search = |use_null_move: bool| {
+            let mut board =
+                MailboxBoard::from_fen("r1b1kb1r/pppp1ppp/5n2/4p3/2BnP2q/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1").unwrap();
+            let mut searcher = Searcher::new();
+            searcher.set_null_move(use_null_move);
+
+            let (m, _) = searcher.search_iterative(&mut board, 4).unwrap();
+            (m.to_uci(), searcher.nodes())
+        };
+
+        let (m, nodes) = search(true);
+        let (plain_move, plain_nodes) = search(false);
+        assert_eq!(m, "f3h4");
+        assert_eq!(m, plain_move);
+        assert!(nodes < plain_nodes, "{} nodes with null moves, {} without", nodes, plain_nodes);
+
+        // with only kings and pawns the side to move might be in zugzwang, so it never passes
+        let board = MailboxBoard::from_fen("8/8/8/4k3/4P3/4K3/8/8 w - - 0 1").unwrap();
+        assert!(!has_pieces(&board, Team::White));
+        assert!(has_pieces(&MailboxBoard::starting_position(), Team::Black));
+
+        // a null-move cutoff reports the score it found rather than beta
+        let mut board =
+            MailboxBoard::from_fen("r1b1kb1r/pppp1ppp/5n2/4p3/2BnP2q/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1").unwrap();
+        assert!(Searcher::new().alpha_beta(&mut board, 4, -101, -100) > -100);
+
+        // with beta a mate score the searcher never passes, so it finds the same mate without null moves
+        let search = |use_null_move: bool| {
+            let mut board = MailboxBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+            let mut searcher = Searcher::new();
+            searcher.set_null_move(use_null_move);
+            searcher.alpha_beta(&mut board, 4, MATE_SCORE - 4, MATE_SCORE - 3)
+        };
+        assert_eq!(search(true), MATE_SCORE - 1);
+        assert_eq!(search(true), search(false));
+    }
+
+    #[test]
+    fn test_principal_variation() {
+        let mut board = MailboxBoard::from_fen("r1b1kb1r/pppp1ppp/5n2/4p3/2BnP2q/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1").unwrap();
+        let fen = board.to_fen();
+        let mut searcher = Searcher::new();
+        let best = searcher.best_move(&mut board, 4);
+
+        let line = searcher.principal_variation();
+        assert_eq!(line.len(), 4);
+        assert_eq!(line.first().copied(), best);
+        assert_eq!(board.to_fen(), fen);
+
+        for m in line {
+            assert!(board.legal_moves().contains(&m), "{}", m.to_uci());
+            board.make_move(m);
+        }
+
+        // with a table, the line still starts from the best move of the deepest search
+        let mut board = MailboxBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut searcher = Searcher::with_table(TranspositionTable::new(1 << 12));
+        let (m, _) = searcher.search_iterative(&mut board, 3).unwrap();
+        assert_eq!(searcher.principal_variation(), [m]);
+        assert!(Searcher::new().principal_variation().is_empty());
+    }
+
+    #[test]
+    fn test_search_timed() {
+        let limit = Duration::from_millis(200);
+        let mut board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let fen = board.to_fen();
+
+        let start = Instant::now();
+        let (m, _) = search_timed(&mut board, limit).unwrap();
+        assert!(start.elapsed() < limit * 5, "took {:?}", start.elapsed());
+        assert!(board.legal_moves().contains(&m));
+        assert_eq!(board.to_fen(), fen);
+
+        // even no time at all gets a move from the first depth
+        let mut board = MailboxBoard::starting_position();
+        let (m, _) = search_timed(&mut board, Duration::ZERO).unwrap();
+        assert!(board.legal_moves().contains(&m));
+        let mut mated = MailboxBoard::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(search_timed(&mut mated, limit), None);
+
+        // a depth cut short by the clock leaves the line of the last finished depth, which starts with the move
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+        ] {
+            let mut board = MailboxBoard::from_fen(fen).unwrap();
+            for millis in 1..60 {
+                let mut searcher = Searcher::new();
+                let (m, _) = searcher.search_timed(&mut board, Duration::from_millis(millis)).unwrap();
+                assert_eq!(searcher.principal_variation().first(), Some(&m), "{} after {} ms", fen, millis);
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_timed_without_quiescence() {
+        // the leaves count nodes without reading the clock, which mustn't stop it from being read at all
+        let limit = Duration::from_millis(200);
+        let mut board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let config = SearchConfig { max_depth: MAX_DEPTH, time_limit: Some(limit), quiescence: false, ..SearchConfig::default() };
+
+        let start = Instant::now();
+        let (m, _) = Searcher::new().search(&mut board, &config).unwrap();
+        assert!(start.elapsed() < limit * 5, "took {:?}", start.elapsed());
+        assert!(board.legal_moves().contains(&m));
+    }
+
+    #[test]
+    fn test_aspiration_windows() {
+        for fen in [
+            "r1b1kb1r/pppp1ppp/5n2/4p3/2BnP2q/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        ] {
+            let mut board = MailboxBoard::from_fen(fen).unwrap();
+            let mut narrow = Searcher::new();
+            let mut full = Searcher::new();
+            full.set_aspiration_windows(false);
+
+            let (_, score) = narrow.search_iterative(&mut board, 4).unwrap();
+            let (_, full_score) = full.search_iterative(&mut board, 4).unwrap();
+            assert_eq!(score, full_score, "{}", fen);
+            assert_eq!(board.to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_check_extensions() {
+        // 1. Nh6+ is a double check, then 1... Kh8 2. Qg8+ Rxg8 3. Nf7# is a smothered mate, and 1... Kf8 2. Qf7#
+        let fen = "r5k1/5Npp/8/8/2Q5/8/8/6K1 w - - 0 1";
+
+        let mut board = MailboxBoard::from_fen(fen).unwrap();
+        let (m, score) = Searcher::new().search_iterative(&mut board, 3).unwrap();
+        assert_eq!(m.to_uci(), "f7h6");
+        assert_eq!(score, MATE_SCORE - 5);
+
+        // without the extensions the mate is past the horizon at this depth
+        let mut searcher = Searcher::new();
+        searcher.set_check_extensions(false);
+        let (_, score) = searcher.search_iterative(&mut board, 3).unwrap();
+        assert!(score < MATE_THRESHOLD, "{}", score);
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_repetition_draw() {
+        // Black is a queen and rook up, but White can check forever with Qe8+ Kh7 Qh5+ Kg8
+        let mut board = MailboxBoard::from_fen("4Q1k1/6p1/8/8/8/2r5/1q4PP/7K b - - 0 1").unwrap();
+        board.apply_uci_moves("g8h7 e8h5 h7g8").unwrap();
+
+        let (m, score) = search_iterative(&mut board, 3).unwrap();
+        assert_eq!(m.to_uci(), "h5e8");
+        assert_eq!(score, 0);
+
+        // without the earlier moves, the search has to go deep enough to come back to the root itself
+        let mut fresh = MailboxBoard::from_fen(&board.to_fen()).unwrap();
+        let (_, score) = search_iterative(&mut fresh, 1).unwrap();
+        assert!(score < 0, "{}", score);
+        assert_eq!(search_iterative(&mut fresh, 3).map(|(m, score)| (m.to_uci(), score)), Some((String::from("h5e8"), 0)));
+    }
+
+    #[test]
+    fn test_search_config() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let depth_only = SearchConfig { max_depth: 3, ..SearchConfig::default() };
+        let timed = SearchConfig { max_depth: 64, time_limit: Some(Duration::from_millis(100)), ..SearchConfig::default() };
+        let bare = SearchConfig { max_depth: 3, null_move: false, quiescence: false, ..SearchConfig::default() };
+
+        for config in [depth_only, timed, bare] {
+            let mut board = MailboxBoard::from_fen(fen).unwrap();
+            let (m, _) = search(&mut board, &config).unwrap();
+            assert!(board.legal_moves().contains(&m), "{:?}", config);
+            assert_eq!(board.to_fen(), fen);
+        }
+
+        // leaving out quiescence scores the leaves as they stand, which is fewer nodes
+        let mut board = MailboxBoard::from_fen(fen).unwrap();
+        let mut with = Searcher::new();
+        let mut without = Searcher::new();
+        with.search(&mut board, &depth_only);
+        without.search(&mut board, &SearchConfig { quiescence: false, ..depth_only });
+        assert!(without.nodes() < with.nodes());
+
+        let mut mated = MailboxBoard::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(search(&mut mated, &timed), None);
+
+        // the config doesn't stick to the searcher for the searches after it
+        let mut searcher = Searcher::new();
+        searcher.set_null_move(false);
+        let everything_off = SearchConfig {
+            null_move: false,
+            quiescence: false,
+            aspiration_windows: false,
+            check_extensions: false,
+            ..depth_only
+        };
+        searcher.search(&mut board, &everything_off);
+        assert!(!searcher.use_null_move);
+        assert!(searcher.use_quiescence && searcher.use_aspiration_windows && searcher.use_check_extensions);
+
+        let mut fresh = Searcher::new();
+        fresh.set_null_move(false);
+        assert_eq!(searcher.search_iterative(&mut board, 3), fresh.search_iterative(&mut board, 3));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_search_parallel_matches_serial() {
+        for fen in [
+            "r1b1kb1r/pppp1ppp/5n2/4p3/2BnP2q/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1",
+            "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ] {
+            let mut board = MailboxBoard::from_fen(fen).unwrap();
+            let mut moves = board.legal_moves();
+            order_moves(&board, &mut moves);
+            let (i, score) = Searcher::new().search_root(&mut board, &moves, 3).unwrap();
+
+            assert_eq!(search_parallel(&board, 3), Some((moves[i], score)), "{}", fen);
+        }
+
+        let mated = MailboxBoard::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(search_parallel(&mated, 3), None);
+    }
+}
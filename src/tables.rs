@@ -0,0 +1,61 @@
+use crate::board::{BOARD_LENGTH, NUM_TILES};
+use crate::movegen::{KING_OFFSETS, KNIGHT_OFFSETS};
+
+/// works out the squares reached by one step of each offset from every square
+const fn step_table(offsets: &[(i8, i8); 8]) -> [u64; NUM_TILES] {
+    let mut table = [0; NUM_TILES];
+
+    let mut square = 0;
+    while square < NUM_TILES {
+        let rank = (square / BOARD_LENGTH) as i8;
+        let file = (square % BOARD_LENGTH) as i8;
+
+        let mut i = 0;
+        while i < offsets.len() {
+            let (target_rank, target_file) = (rank + offsets[i].0, file + offsets[i].1);
+            if target_rank >= 0 && target_rank < BOARD_LENGTH as i8 && target_file >= 0 && target_file < BOARD_LENGTH as i8 {
+                table[square] |= 1 << (target_rank as usize * BOARD_LENGTH + target_file as usize);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+
+    table
+}
+
+/// The squares a knight attacks from each square, as bitboards indexed by the square's index
+pub const KNIGHT_ATTACKS: [u64; NUM_TILES] = step_table(&KNIGHT_OFFSETS);
+
+/// The squares a king attacks from each square, as bitboards indexed by the square's index
+pub const KING_ATTACKS: [u64; NUM_TILES] = step_table(&KING_OFFSETS);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Coordinate;
+
+    /// gets the bitboard with the named squares set
+    fn bits(squares: &[&str]) -> u64 {
+        squares.iter().fold(0, |bits, square| bits | 1 << Coordinate::from_algebraic(square).unwrap().index())
+    }
+
+    /// gets the index of a named square
+    fn index(square: &str) -> usize {
+        Coordinate::from_algebraic(square).unwrap().index() as usize
+    }
+
+    #[test]
+    fn test_knight_attacks() {
+        assert_eq!(KNIGHT_ATTACKS[index("b1")], bits(&["a3", "c3", "d2"]));
+        assert_eq!(KNIGHT_ATTACKS[index("h8")], bits(&["g6", "f7"]));
+        assert_eq!(KNIGHT_ATTACKS[index("d4")].count_ones(), 8);
+    }
+
+    #[test]
+    fn test_king_attacks() {
+        assert_eq!(KING_ATTACKS[index("a1")], bits(&["a2", "b1", "b2"]));
+        assert_eq!(KING_ATTACKS[index("e8")], bits(&["d8", "f8", "d7", "e7", "f7"]));
+        assert_eq!(KING_ATTACKS[index("e4")].count_ones(), 8);
+    }
+}
@@ -0,0 +1,141 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::board::Move;
+
+/// How a stored score relates to the position's real score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// the score is exact
+    Exact,
+    /// the search failed high, so the real score is at least this
+    LowerBound,
+    /// the search failed low, so the real score is at most this
+    UpperBound,
+}
+
+/// The result of searching a position, as kept in a `TranspositionTable`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    hash: u64,
+    depth: u8,
+    score: i32,
+    flag: Flag,
+    best_move: Option<Move>,
+}
+
+impl Entry {
+    /// Creates an entry for a position searched to `depth`
+    pub fn new(hash: u64, depth: u8, score: i32, flag: Flag, best_move: Option<Move>) -> Self {
+        Entry { hash, depth, score, flag, best_move }
+    }
+
+    /// gets the Zobrist hash of the position searched
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// gets the depth the position was searched to
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// gets the score found, from the point of view of the side to move
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    /// gets whether the score is exact or a bound
+    pub fn flag(&self) -> Flag {
+        self.flag
+    }
+
+    /// gets the best move found, if any move was searched
+    pub fn best_move(&self) -> Option<Move> {
+        self.best_move
+    }
+}
+
+/// A fixed number of search results, each position going in the slot at its hash modulo the size
+///
+/// A table with no slots never stores anything.
+#[derive(Debug, Default, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    /// Creates an empty table with room for `size` entries
+    pub fn new(size: usize) -> Self {
+        TranspositionTable { entries: vec![None; size] }
+    }
+
+    /// gets the number of slots in the table
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// gets the slot a hash goes in
+    fn slot(&self, hash: u64) -> Option<usize> {
+        match self.entries.len() {
+            0 => None,
+            size => Some((hash % size as u64) as usize),
+        }
+    }
+
+    /// Looks up the entry for a position, if it's still in the table
+    pub fn probe(&self, hash: u64) -> Option<&Entry> {
+        self.entries[self.slot(hash)?].as_ref().filter(|entry| entry.hash == hash)
+    }
+
+    /// Stores an entry, replacing whatever was in its slot unless that was a deeper search of the same position
+    pub fn store(&mut self, entry: Entry) {
+        if let Some(slot) = self.slot(entry.hash) {
+            let existing = &mut self.entries[slot];
+            if existing.is_none_or(|old| old.hash != entry.hash || old.depth <= entry.depth) {
+                *existing = Some(entry);
+            }
+        }
+    }
+
+    /// Empties every slot
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_and_store() {
+        let mut table = TranspositionTable::new(16);
+        assert_eq!(table.probe(5), None);
+
+        let entry = Entry::new(5, 3, 120, Flag::Exact, None);
+        table.store(entry);
+        assert_eq!(table.probe(5), Some(&entry));
+
+        // a different position in the same slot isn't mistaken for this one, and replaces it
+        assert_eq!(table.probe(21), None);
+        let other = Entry::new(21, 1, -40, Flag::UpperBound, None);
+        table.store(other);
+        assert_eq!(table.probe(5), None);
+        assert_eq!(table.probe(21), Some(&other));
+
+        // a shallower search of the same position doesn't replace a deeper one
+        table.store(Entry::new(21, 0, 10, Flag::LowerBound, None));
+        assert_eq!(table.probe(21), Some(&other));
+
+        table.clear();
+        assert_eq!(table.probe(21), None);
+    }
+
+    #[test]
+    fn test_empty_table() {
+        let mut table = TranspositionTable::default();
+        table.store(Entry::new(5, 3, 120, Flag::Exact, None));
+        assert_eq!(table.size(), 0);
+        assert_eq!(table.probe(5), None);
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{self, BufRead, Write};
+
+use crate::board::MailboxBoard;
+use crate::search::{SearchConfig, Searcher};
+use crate::transposition::TranspositionTable;
+
+/// how many entries the transposition table gets
+const TABLE_SIZE: usize = 1 << 16;
+
+/// how deep to search when `go` doesn't give a depth
+const DEFAULT_DEPTH: u8 = 4;
+
+/// sets up the position from the arguments of a `position` command, or None if they don't make sense
+///
+/// The arguments are `startpos` or `fen` and the six FEN fields, followed by an optional `moves` and the moves
+/// played since then in UCI notation. Moves after the first one that can't be played are left off.
+fn parse_position(args: &[&str]) -> Option<MailboxBoard> {
+    let (mut board, rest) = match args {
+        ["startpos", rest @ ..] => (MailboxBoard::starting_position(), rest),
+        ["fen", rest @ ..] => {
+            let end = rest.iter().position(|&arg| arg == "moves").unwrap_or(rest.len());
+            (MailboxBoard::from_fen(&rest[..end].join(" ")).ok()?, &rest[end..])
+        }
+        _ => return None,
+    };
+
+    if let ["moves", moves @ ..] = rest {
+        // the moves before a bad one are still played, so there's nothing more to do about it
+        board.apply_uci_moves(&moves.join(" ")).ok();
+    }
+
+    Some(board)
+}
+
+/// gets the search settings asked for by the arguments of a `go` command, which only sets the depth so far
+fn parse_go(args: &[&str]) -> SearchConfig {
+    let max_depth = args
+        .windows(2)
+        .find(|pair| pair[0] == "depth")
+        .and_then(|pair| pair[1].parse().ok())
+        .unwrap_or(DEFAULT_DEPTH);
+    SearchConfig { max_depth, ..SearchConfig::default() }
+}
+
+/// Talks to a chess GUI over the Universal Chess Interface, reading commands and writing replies until `quit`
+///
+/// `uci`, `isready`, `ucinewgame`, `position` and `go depth N` are understood, and anything else is ignored. A
+/// `go` searches the current position and replies with `bestmove`, or `bestmove 0000` when there are no legal
+/// moves. Returns early if reading or writing fails.
+pub fn run_uci<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut board = MailboxBoard::starting_position();
+    let mut searcher = Searcher::with_table(TranspositionTable::new(TABLE_SIZE));
+
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words[..] {
+            ["uci", ..] => {
+                writeln!(output, "id name {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))?;
+                writeln!(output, "uciok")?;
+            }
+            ["isready", ..] => writeln!(output, "readyok")?,
+            ["ucinewgame", ..] => {
+                board = MailboxBoard::starting_position();
+                searcher = Searcher::with_table(TranspositionTable::new(TABLE_SIZE));
+            }
+            ["position", ref args @ ..] => {
+                if let Some(position) = parse_position(args) {
+                    board = position;
+                }
+            }
+            ["go", ref args @ ..] => match searcher.search(&mut board, &parse_go(args)) {
+                Some((m, _)) => writeln!(output, "bestmove {}", m.to_uci())?,
+                None => writeln!(output, "bestmove 0000")?,
+            },
+            ["quit", ..] => break,
+            _ => {}
+        }
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// runs the commands and gets the lines written back
+    fn run(commands: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        run_uci(Cursor::new(commands), &mut output).unwrap();
+        String::from_utf8(output).unwrap().lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_run_uci_handshake() {
+        let lines = run("uci\nisready\nquit\ngo depth 1\n");
+        assert_eq!(lines.last().map(String::as_str), Some("readyok"));
+        assert!(lines.iter().any(|line| line == "uciok"));
+    }
+
+    #[test]
+    fn test_run_uci_go() {
+        let lines = run("ucinewgame\nposition startpos moves e2e4 e7e5\ngo depth 2\n");
+        let m = lines.iter().find_map(|line| line.strip_prefix("bestmove ")).expect("no bestmove");
+
+        let mut board = MailboxBoard::starting_position();
+        for uci in ["e2e4", "e7e5"] {
+            board.make_move(board.parse_uci(uci).unwrap());
+        }
+        assert!(board.legal_moves().iter().any(|legal| legal.to_uci() == m), "{}", m);
+
+        // Ra8 is a back rank mate
+        let lines = run("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 2\n");
+        assert_eq!(lines, ["bestmove a1a8"]);
+
+        // the moves after the fen are played, leaving Black checkmated
+        let lines = run("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1 moves a1a8\ngo depth 1\n");
+        assert_eq!(lines, ["bestmove 0000"]);
+    }
+}
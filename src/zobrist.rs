@@ -0,0 +1,174 @@
+use crate::board::{BOARD_LENGTH, CastleSide, Coordinate, MailboxBoard, NUM_CHESSMEN, NUM_TEAMS, NUM_TILES, Piece, Team};
+
+/// Random keys used to hash positions
+///
+/// A position's hash is the xor of the keys for each piece on its square, the side to move if it's Black,
+/// each castling right still held, and the file of the en passant square if there is one.
+pub struct Zobrist {
+    pieces: [[u64; NUM_TILES]; NUM_TEAMS * NUM_CHESSMEN],
+    side_to_move: u64,
+    castling: [u64; 4],
+    en_passant: [u64; BOARD_LENGTH],
+}
+
+/// The keys every board hashes with, generated at compile time
+pub static ZOBRIST: Zobrist = Zobrist::generate(0x9e37_79b9_7f4a_7c15);
+
+/// steps a splitmix64 generator, which is plenty random for hash keys
+const fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Zobrist {
+    /// generates a full set of keys from a seed
+    const fn generate(seed: u64) -> Zobrist {
+        let mut state = seed;
+
+        let mut pieces = [[0; NUM_TILES]; NUM_TEAMS * NUM_CHESSMEN];
+        let mut piece = 0;
+        while piece < NUM_TEAMS * NUM_CHESSMEN {
+            let mut square = 0;
+            while square < NUM_TILES {
+                pieces[piece][square] = splitmix64(&mut state);
+                square += 1;
+            }
+            piece += 1;
+        }
+
+        let side_to_move = splitmix64(&mut state);
+
+        let mut castling = [0; 4];
+        let mut right = 0;
+        while right < castling.len() {
+            castling[right] = splitmix64(&mut state);
+            right += 1;
+        }
+
+        let mut en_passant = [0; BOARD_LENGTH];
+        let mut file = 0;
+        while file < BOARD_LENGTH {
+            en_passant[file] = splitmix64(&mut state);
+            file += 1;
+        }
+
+        Zobrist { pieces, side_to_move, castling, en_passant }
+    }
+
+    /// gets the key for a piece on a square, 0 for an empty square
+    pub fn piece(&self, piece: Piece, square: Coordinate) -> u64 {
+        match piece.data() {
+            Some((team, chessman)) => self.pieces[team as usize * NUM_CHESSMEN + chessman as usize][square.index() as usize],
+            None => 0,
+        }
+    }
+
+    /// gets the key mixed in when Black is to move
+    pub fn side_to_move(&self) -> u64 {
+        self.side_to_move
+    }
+
+    /// gets the key for a team's right to castle toward a side
+    pub fn castle(&self, team: Team, side: CastleSide) -> u64 {
+        self.castling[team as usize * 2 + side as usize]
+    }
+
+    /// gets the key for an en passant square, which only depends on its file
+    pub fn en_passant(&self, square: Coordinate) -> u64 {
+        self.en_passant[square.file() as usize]
+    }
+
+    /// Hashes a board from scratch
+    ///
+    /// Boards keep their hash up to date as they change, see `MailboxBoard::zobrist_hash`, this is the slow
+    /// path that's useful for checking it.
+    pub fn hash(&self, board: &MailboxBoard) -> u64 {
+        let mut hash = 0;
+
+        for square in Coordinate::all() {
+            hash ^= self.piece(board.get_tile(square), square);
+        }
+
+        if board.side_to_move() == Team::Black {
+            hash ^= self.side_to_move;
+        }
+
+        for team in Team::all() {
+            for side in [CastleSide::KingSide, CastleSide::QueenSide] {
+                if board.can_castle(team, side) {
+                    hash ^= self.castle(team, side);
+                }
+            }
+        }
+
+        if let Some(square) = board.en_passant() {
+            hash ^= self.en_passant(square);
+        }
+
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// plays UCI moves from the starting position
+    fn play(moves: &[&str]) -> MailboxBoard {
+        let mut board = MailboxBoard::starting_position();
+        for uci in moves {
+            board.make_move(board.parse_uci(uci).expect("bad test move"));
+        }
+        board
+    }
+
+    #[test]
+    fn test_transpositions_hash_equal() {
+        let a = play(&["g1f3", "g8f6", "b1c3"]);
+        let b = play(&["b1c3", "g8f6", "g1f3"]);
+        assert_eq!(a.to_fen(), b.to_fen());
+        assert_eq!(a.zobrist_hash(), b.zobrist_hash());
+
+        let c = play(&["b1c3", "g8f6", "g1h3"]);
+        assert_ne!(a.zobrist_hash(), c.zobrist_hash());
+
+        // same pieces, different side to move
+        let d = play(&["g1f3", "g8f6", "f3g1", "f6g8"]);
+        let e = play(&["g1f3", "g8f6", "f3g1"]);
+        assert_eq!(d.zobrist_hash(), MailboxBoard::starting_position().zobrist_hash());
+        assert_ne!(d.zobrist_hash(), e.zobrist_hash());
+
+        // castling rights and en passant are part of the position
+        let f = play(&["e2e4"]);
+        let mut g = f.clone();
+        g.set_en_passant(None);
+        assert_ne!(f.zobrist_hash(), g.zobrist_hash());
+        g.set_en_passant(f.en_passant());
+        g.revoke_castle(Team::Black, CastleSide::QueenSide);
+        assert_ne!(f.zobrist_hash(), g.zobrist_hash());
+    }
+
+    #[test]
+    fn test_make_unmake_restores_hash() {
+        let mut board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let start = board.zobrist_hash();
+        assert_eq!(start, ZOBRIST.hash(&board));
+
+        for m in board.legal_moves() {
+            let undo = board.make_move(m);
+            assert_eq!(board.zobrist_hash(), ZOBRIST.hash(&board), "{}", m.to_uci());
+
+            for reply in board.legal_moves() {
+                let undo = board.make_move(reply);
+                assert_eq!(board.zobrist_hash(), ZOBRIST.hash(&board), "{} {}", m.to_uci(), reply.to_uci());
+                board.unmake_move(undo);
+            }
+
+            board.unmake_move(undo);
+            assert_eq!(board.zobrist_hash(), start);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Checks that move generation and perft don't allocate for each position, using an allocator that counts
+//! allocations. It's in its own test binary so the counting allocator doesn't stand in for every other test's.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+
+use chess_engine::board::{MailboxBoard, Move};
+use chess_engine::movegen::{MoveList, MAX_MOVES};
+
+thread_local! {
+    /// how many allocations this thread has made, so a test can tell whether some code allocates
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+
+    /// one move buffer for each ply, kept between searches so they're only allocated once
+    static BUFFERS: RefCell<Vec<Vec<Move>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// the system allocator, counting allocations as it goes
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // the count may already be gone while the thread is shutting down
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// runs some code and gets how many allocations it made on this thread
+fn allocations<T>(run: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = run();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+/// counts perft using the first buffer for this ply's moves and the rest for the plies below
+fn perft_into(board: &mut MailboxBoard, depth: u8, buffers: &mut [Vec<Move>]) -> u64 {
+    let Some((moves, rest)) = buffers.split_first_mut().filter(|_| depth > 0) else { return 1 };
+
+    moves.clear();
+    board.generate_moves_into(moves);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for &m in moves.iter() {
+        let undo = board.make_move(m);
+        nodes += perft_into(board, depth - 1, rest);
+        board.unmake_move(undo);
+    }
+    nodes
+}
+
+/// counts perft with the thread's buffers, making more of them first if there aren't enough for the depth
+fn perft_with_thread_buffers(board: &mut MailboxBoard, depth: u8) -> u64 {
+    BUFFERS.with_borrow_mut(|buffers| {
+        while buffers.len() < depth as usize {
+            buffers.push(Vec::with_capacity(MAX_MOVES));
+        }
+        perft_into(board, depth, buffers)
+    })
+}
+
+#[test]
+fn test_generate_moves_into_does_not_allocate() {
+    let board = MailboxBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+
+    let mut buf = Vec::with_capacity(MAX_MOVES);
+    let ((), count) = allocations(|| board.generate_moves_into(&mut buf));
+    assert_eq!(count, 0);
+    assert_eq!(buf.len(), 48);
+
+    let mut list = MoveList::new();
+    let ((), count) = allocations(|| board.generate_moves_into(&mut list));
+    assert_eq!(count, 0);
+    assert_eq!(list.as_slice(), &buf[..]);
+}
+
+#[test]
+fn test_perft_with_thread_local_buffers() {
+    let mut board = MailboxBoard::starting_position();
+
+    // the first run makes the buffers and grows the board's position history to the depth
+    let (nodes, count) = allocations(|| perft_with_thread_buffers(&mut board, 4));
+    assert_eq!(nodes, 197281);
+    assert!(count > 0);
+
+    // after that, not one node allocates
+    let (nodes, count) = allocations(|| perft_with_thread_buffers(&mut board, 4));
+    assert_eq!(nodes, 197281);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_perft_does_not_allocate() {
+    // perft keeps each node's moves in a MoveList, so once the position history has grown to the depth nothing
+    // else is allocated
+    let mut board = MailboxBoard::starting_position();
+    board.perft(4);
+
+    let (nodes, count) = allocations(|| board.perft(4));
+    assert_eq!(nodes, 197281);
+    assert_eq!(count, 0);
+}